@@ -0,0 +1,226 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::board::{Board, WinningLine};
+
+/// When and with which score a board wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    pub board: usize,
+    /// Index into the draws of the number which completes the board.
+    pub turn: usize,
+    pub score: u32,
+}
+
+/// Maps every drawn number to the index at which it is first drawn.
+fn draw_indices(draws: &[u32]) -> HashMap<u32, usize> {
+    let mut indices = HashMap::with_capacity(draws.len());
+    for (index, &number) in draws.iter().enumerate() {
+        indices.entry(number).or_insert(index);
+    }
+
+    indices
+}
+
+fn win_turn_with(board: &Board, indices: &HashMap<u32, usize>) -> Option<usize> {
+    // A line is complete once its last unmarked number is drawn, the board is
+    // complete once its first line is. A board which has already won doesn't
+    // win again.
+    if board.is_completed() {
+        return None;
+    }
+
+    let unmarked = board.unmarked_numbers().collect::<HashSet<_>>();
+
+    board.lines()
+        .filter_map(|(_, line)| line.iter()
+            .filter(|num| unmarked.contains(num))
+            .map(|num| indices.get(num).copied())
+            .collect::<Option<Vec<_>>>()
+            .and_then(|turns| turns.into_iter().max()))
+        .min()
+}
+
+/// The index into the draws at which the board wins, None if it never does.
+pub fn win_turn(board: &Board, draws: &[u32]) -> Option<usize> {
+    win_turn_with(board, &draw_indices(draws))
+}
+
+/// The shortest prefix of the draws after which the board wins.
+pub fn winning_prefix<'a>(board: &Board, draws: &'a [u32]) -> Option<&'a [u32]> {
+    win_turn(board, draws).map(|turn| &draws[..=turn])
+}
+
+/// The outcome of every board which wins, in the order the game would report
+/// them.
+pub fn outcomes(boards: &[Board], draws: &[u32]) -> Vec<Outcome> {
+    let indices = draw_indices(draws);
+
+    let mut outcomes = boards.iter()
+        .enumerate()
+        .filter_map(|(index, board)| {
+            let turn = win_turn_with(board, &indices)?;
+            let unmarked_sum = board.unmarked_numbers()
+                .filter(|num| indices.get(num).is_none_or(|&t| t > turn))
+                .sum::<u32>();
+
+            Some(Outcome { board: index, turn, score: unmarked_sum * draws[turn] })
+        })
+        .collect::<Vec<_>>();
+
+    outcomes.sort_by_key(|o| (o.turn, o.board));
+    outcomes
+}
+
+/// Find an ordering of the draws in which the chosen board is the first to
+/// win. Returns None if no such ordering exists, there is no such board or
+/// it has already won.
+///
+/// Drawing the unmarked numbers of one of its lines first is optimal for the
+/// chosen board: any other number can only help the other boards. Another
+/// board completes a line by then if the line needs nothing else. It must not
+/// have a lower index, which would win a tie, and its line has to need the
+/// number drawn last, or it would win before the chosen board.
+pub fn winning_order(boards: &[Board], chosen: usize, draws: &[u32]) -> Option<Vec<u32>> {
+    let board = boards.get(chosen)?;
+    if board.is_completed() {
+        return None;
+    }
+
+    let available = draws.iter().collect::<HashSet<_>>();
+    let needed = |board: &Board| {
+        let unmarked = board.unmarked_numbers().collect::<HashSet<_>>();
+        board.lines()
+            .map(|(_, line)| line.into_iter().filter(|num| unmarked.contains(num)).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+
+    let competing_lines = boards.iter()
+        .enumerate()
+        .filter(|&(index, other)| index != chosen && !other.is_completed())
+        .flat_map(|(index, other)| needed(other).into_iter().map(move |line| (index, line)))
+        .collect::<Vec<_>>();
+
+    let (mut order, last) = needed(board).into_iter()
+        .filter(|line| line.iter().all(|num| available.contains(num)))
+        .find_map(|line| {
+            let completed = competing_lines.iter()
+                .filter(|(_, other)| other.iter().all(|num| line.contains(num)))
+                .collect::<Vec<_>>();
+
+            if completed.iter().any(|&&(index, _)| index < chosen) {
+                return None;
+            }
+
+            let last = *line.iter().rev().find(|num| completed.iter().all(|(_, other)| other.contains(num)))?;
+            let first = line.into_iter().filter(|&num| num != last).collect::<Vec<_>>();
+
+            Some((first, last))
+        })?;
+
+    order.push(last);
+    for &number in draws {
+        if !order.contains(&number) {
+            order.push(number);
+        }
+    }
+
+    Some(order)
+}
+
+/// The line with which the board wins given the draws, if it wins at all.
+pub fn winning_line(board: &Board, draws: &[u32]) -> Option<WinningLine> {
+    let indices = draw_indices(draws);
+    let turn = win_turn_with(board, &indices)?;
+    let unmarked = board.unmarked_numbers().collect::<HashSet<_>>();
+
+    board.lines()
+        .find(|(_, line)| line.iter()
+            .all(|num| !unmarked.contains(num) || indices.get(num).is_some_and(|&t| t <= turn)))
+        .map(|(winning_line, _)| winning_line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BOARD_SIZE;
+    use crate::game::Game;
+    use crate::state::GameState;
+
+    const DRAWS: [u32; 27] = [
+        7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8,
+        19, 3, 26, 1,
+    ];
+
+    fn boards() -> Vec<Board> {
+        let lines = [
+            "22 13 17 11  0", " 8  2 23  4 24", "21  9 14 16  7", " 6 10  3 18  5",
+            " 1 12 20 15 19", "", " 3 15  0  2 22", " 9 18 13 17  5", "19  8  7 25 23",
+            "20 11 10 24  4", "14 21 16 12  6", "", "14 21 17 24  4", "10 16 15  9 19",
+            "18  8 23 26 20", "22 11 13  6  5", " 2  0 12  3  7",
+        ].map(|l| l.to_owned());
+
+        lines.chunks(BOARD_SIZE + 1)
+            .map(Board::new)
+            .collect()
+    }
+
+    #[test]
+    fn test_outcomes_match_simulation() {
+        let outcomes = outcomes(&boards(), &DRAWS);
+
+        assert_eq!(Outcome { board: 2, turn: 11, score: 4512 }, outcomes[0]);
+        assert_eq!(Outcome { board: 1, turn: 14, score: 1924 }, outcomes[2]);
+    }
+
+    #[test]
+    fn test_winning_prefix() {
+        let boards = boards();
+
+        assert_eq!(Some(&DRAWS[..12]), winning_prefix(&boards[2], &DRAWS));
+        assert_eq!(Some(WinningLine::Row(0)), winning_line(&boards[2], &DRAWS));
+        assert_eq!(None, win_turn(&boards[2], &DRAWS[..11]));
+    }
+
+    #[test]
+    fn test_winning_order() {
+        let boards = boards();
+        let order = winning_order(&boards, 1, &DRAWS).unwrap();
+
+        assert_eq!(DRAWS.len(), order.len());
+        assert_eq!(1, outcomes(&boards, &order)[0].board);
+        assert_eq!(None, winning_order(&boards, boards.len(), &DRAWS));
+    }
+
+    #[test]
+    fn test_resumed_game_keeps_its_marks() {
+        let mut game = Game::new(boards(), DRAWS.into());
+        for _ in 0..12 {
+            game.next_draw();
+        }
+        let state = game.state();
+
+        // Board 2 has already won, the others continue from their marks.
+        let remaining = outcomes(&boards(), &DRAWS)[1..].iter()
+            .map(|o| Outcome { turn: o.turn - 12, ..*o })
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, outcomes(game.boards(), &state.draws));
+        assert_eq!(None, win_turn(&game.boards()[2], &state.draws));
+        assert_eq!(None, winning_order(game.boards(), 2, &state.draws));
+    }
+
+    #[test]
+    fn test_winning_order_of_resumed_game() {
+        let mut game = Game::new(boards(), DRAWS.into());
+        for _ in 0..5 {
+            game.next_draw();
+        }
+
+        for chosen in 0..3 {
+            let order = winning_order(game.boards(), chosen, &game.state().draws).unwrap();
+            let mut resumed = Game::from(GameState { draws: order, ..game.state() });
+            resumed.by_ref().for_each(drop);
+
+            assert_eq!(chosen, resumed.results()[0].board);
+        }
+    }
+}
@@ -2,8 +2,25 @@ use std::{collections::HashMap, fmt::Debug};
 
 pub const BOARD_SIZE: usize = 5;
 
+/// The row or column which completed a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinningLine {
+    Row(usize),
+    Column(usize),
+}
+
+impl WinningLine {
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        match *self {
+            WinningLine::Row(r) => r == row,
+            WinningLine::Column(c) => c == col,
+        }
+    }
+}
+
 pub struct Board {
-    completed: bool,
+    numbers: [[u32; BOARD_SIZE]; BOARD_SIZE],
+    winning_line: Option<WinningLine>,
     marked_in_column: [usize; BOARD_SIZE],
     marked_in_row: [usize; BOARD_SIZE],
     unmarked_numbers: HashMap<u32, (usize, usize)>,
@@ -12,81 +29,216 @@ pub struct Board {
 
 impl Board {
     pub fn new(input: &[String]) -> Self {
-        let mut numbers = HashMap::with_capacity(BOARD_SIZE * BOARD_SIZE);
+        let mut numbers = [[0; BOARD_SIZE]; BOARD_SIZE];
+        let mut count = 0;
 
-        for line in 0..BOARD_SIZE {
-            input[line].split(' ')
-                .filter(|num| num.len() != 0) // Double spaces in the input file.
+        for (row, line) in input.iter().take(BOARD_SIZE).enumerate() {
+            line.split(' ')
+                .filter(|num| !num.is_empty()) // Double spaces in the input file.
                 .map(|num| num.parse::<u32>().expect("Failed to parse number."))
                 .enumerate()
                 .for_each(|(col, num)| {
-                    numbers.insert(num, (line, col));
+                    numbers[row][col] = num;
+                    count += 1;
                 });
         }
 
-        assert!(numbers.len() == BOARD_SIZE * BOARD_SIZE, "Board size was {}", numbers.len());
+        assert!(count == BOARD_SIZE * BOARD_SIZE, "Board size was {}", count);
+
+        Self::from_numbers(numbers)
+    }
+
+    pub fn from_numbers(numbers: [[u32; BOARD_SIZE]; BOARD_SIZE]) -> Self {
+        let mut unmarked_numbers = HashMap::with_capacity(BOARD_SIZE * BOARD_SIZE);
+        for (row, line) in numbers.iter().enumerate() {
+            for (col, &num) in line.iter().enumerate() {
+                unmarked_numbers.insert(num, (row, col));
+            }
+        }
+
+        assert!(
+            unmarked_numbers.len() == BOARD_SIZE * BOARD_SIZE,
+            "Board contains duplicate numbers."
+        );
 
         Self {
-            completed: false,
+            numbers,
+            winning_line: None,
             marked_in_column: [0; BOARD_SIZE],
             marked_in_row: [0; BOARD_SIZE],
-            unmarked_numbers: numbers,
+            unmarked_numbers,
             marked_numbers: HashMap::with_capacity(BOARD_SIZE * BOARD_SIZE),
         }
     }
 
-    /// Mark the given number on this board. Returns Some(score) if the board is
-    /// completed, None otherwise.
-    pub fn try_complete(&mut self, number: u32) -> Option<u32> {
-        if self.completed {
+    /// Recreate a board from its numbers and which of them are marked.
+    pub fn restore(
+        numbers: [[u32; BOARD_SIZE]; BOARD_SIZE],
+        marked: [[bool; BOARD_SIZE]; BOARD_SIZE],
+    ) -> Self {
+        let mut board = Self::from_numbers(numbers);
+
+        for (row, line) in marked.iter().enumerate() {
+            for (col, _) in line.iter().enumerate().filter(|(_, &m)| m) {
+                let number = numbers[row][col];
+                board.unmarked_numbers.remove(&number);
+                board.marked_numbers.insert(number, (row, col));
+                board.marked_in_row[row] += 1;
+                board.marked_in_column[col] += 1;
+            }
+        }
+
+        board.winning_line = board.marked_in_row.iter()
+            .position(|&marked| marked == BOARD_SIZE)
+            .map(WinningLine::Row)
+            .or_else(|| board.marked_in_column.iter()
+                .position(|&marked| marked == BOARD_SIZE)
+                .map(WinningLine::Column));
+
+        board
+    }
+
+    /// Mark the given number on this board. Returns the position of the number
+    /// if it was marked, None if it is not on the board or the board is
+    /// already completed.
+    pub fn mark(&mut self, number: u32) -> Option<(usize, usize)> {
+        if self.is_completed() {
             return None;
         }
-        
-        let map_entry = self.unmarked_numbers.remove(&number);
 
-        match map_entry {
-            Some((row, col)) => {
-                self.marked_in_column[col] += 1;
-                self.marked_in_row[row] += 1;
-                self.marked_numbers.insert(number, (row, col));
+        let (row, col) = self.unmarked_numbers.remove(&number)?;
+
+        self.marked_in_column[col] += 1;
+        self.marked_in_row[row] += 1;
+        self.marked_numbers.insert(number, (row, col));
 
-                if self.marked_in_column[col] == BOARD_SIZE || self.marked_in_row[row] == BOARD_SIZE {
-                    self.completed = true;
+        if self.marked_in_row[row] == BOARD_SIZE {
+            self.winning_line = Some(WinningLine::Row(row));
+        } else if self.marked_in_column[col] == BOARD_SIZE {
+            self.winning_line = Some(WinningLine::Column(col));
+        }
 
-                    let unmarked_sum = self.unmarked_numbers.keys()
-                        .sum::<u32>();
+        Some((row, col))
+    }
 
-                    let score = unmarked_sum * number;
-                    Some(score)
-                } else {
-                    None
-                }
-            },
-            None => None,
+    /// The score of this board if it were completed by the given number.
+    pub fn score(&self, number: u32) -> u32 {
+        self.unmarked_numbers.keys().sum::<u32>() * number
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.winning_line.is_some()
+    }
+
+    pub fn winning_line(&self) -> Option<WinningLine> {
+        self.winning_line
+    }
+
+    pub fn number_at(&self, row: usize, col: usize) -> u32 {
+        self.numbers[row][col]
+    }
+
+    pub fn is_marked(&self, row: usize, col: usize) -> bool {
+        self.marked_numbers.contains_key(&self.numbers[row][col])
+    }
+
+    pub fn grid(&self) -> [[u32; BOARD_SIZE]; BOARD_SIZE] {
+        self.numbers
+    }
+
+    pub fn marks(&self) -> [[bool; BOARD_SIZE]; BOARD_SIZE] {
+        let mut marks = [[false; BOARD_SIZE]; BOARD_SIZE];
+        for &(row, col) in self.marked_numbers.values() {
+            marks[row][col] = true;
         }
+
+        marks
+    }
+
+    /// All rows followed by all columns of this board.
+    pub fn lines(&self) -> impl Iterator<Item = (WinningLine, [u32; BOARD_SIZE])> + '_ {
+        let rows = (0..BOARD_SIZE)
+            .map(|row| (WinningLine::Row(row), self.numbers[row]));
+        let columns = (0..BOARD_SIZE)
+            .map(|col| (WinningLine::Column(col), self.numbers.map(|line| line[col])));
+
+        rows.chain(columns)
+    }
+
+    /// The numbers which are not marked yet, in no particular order.
+    pub fn unmarked_numbers(&self) -> impl Iterator<Item = u32> + '_ {
+        self.unmarked_numbers.keys().copied()
     }
 }
 
 impl Debug for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let numbers = self.unmarked_numbers.iter()
-            .chain(self.marked_numbers.iter())
-            .map(|(k, v)| (v, k))
-            .collect::<HashMap<_,_>>();
-
         for row in 0..BOARD_SIZE {
             for col in 0..BOARD_SIZE {
-                let number = numbers[&(row, col)];
+                let number = self.number_at(row, col);
 
-                if self.unmarked_numbers.contains_key(number) {
-                    write!(f, " {:02}  ", number)?;
-                } else {
+                if self.is_marked(row, col) {
                     write!(f, "|{:02}| ", number)?;
+                } else {
+                    write!(f, " {:02}  ", number)?;
                 }
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> Board {
+        Board::new(&[
+            "14 21 17 24  4".to_owned(),
+            "10 16 15  9 19".to_owned(),
+            "18  8 23 26 20".to_owned(),
+            "22 11 13  6  5".to_owned(),
+            " 2  0 12  3  7".to_owned(),
+        ])
+    }
+
+    #[test]
+    fn test_board_completes_on_row() {
+        let mut board = board();
+
+        for number in [7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21] {
+            board.mark(number);
+            assert!(!board.is_completed());
+        }
+
+        board.mark(24);
+        assert_eq!(Some(WinningLine::Row(0)), board.winning_line());
+        assert_eq!(4512, board.score(24));
+        assert_eq!(None, board.mark(10));
+    }
+
+    #[test]
+    fn test_mark_reports_position() {
+        let mut board = board();
+
+        assert_eq!(Some((2, 3)), board.mark(26));
+        assert_eq!(None, board.mark(26));
+        assert_eq!(None, board.mark(99));
+        assert!(board.is_marked(2, 3));
+    }
+
+    #[test]
+    fn test_restore_keeps_marks() {
+        let mut board = board();
+        for number in [4, 10, 16, 15, 9, 19] {
+            board.mark(number);
+        }
+
+        let restored = Board::restore(board.grid(), board.marks());
+        assert_eq!(Some(WinningLine::Row(1)), restored.winning_line());
+        assert!(restored.is_marked(0, 4));
+        assert_eq!(board.score(19), restored.score(19));
+    }
+}
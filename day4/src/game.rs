@@ -0,0 +1,151 @@
+use std::collections::VecDeque;
+
+use crate::board::Board;
+use crate::state::{GameState, Win};
+
+/// Everything that happens during a game of bingo, in the order it happens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Drawn(u32),
+    Marked { board: usize, row: usize, col: usize },
+    Won { board: usize, score: u32 },
+}
+
+/// A game of bingo which is played by iterating over its events.
+pub struct Game {
+    boards: Vec<Board>,
+    moves: VecDeque<u32>,
+    pending: VecDeque<Event>,
+    results: Vec<Win>,
+}
+
+impl Game {
+    pub fn new(boards: Vec<Board>, moves: VecDeque<u32>) -> Self {
+        Self {
+            boards,
+            moves,
+            pending: VecDeque::new(),
+            results: vec![],
+        }
+    }
+
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    /// The boards which have won so far, in the order they won.
+    pub fn results(&self) -> &[Win] {
+        &self.results
+    }
+
+    /// A snapshot of the game after the last drawn number.
+    pub fn state(&self) -> GameState {
+        GameState {
+            boards: self.boards.iter().map(|b| b.into()).collect(),
+            draws: self.moves.iter().copied().collect(),
+            results: self.results.clone(),
+        }
+    }
+
+    /// All events caused by the next drawn number, None if no numbers are
+    /// left to draw.
+    pub fn next_draw(&mut self) -> Option<Vec<Event>> {
+        if self.pending.is_empty() {
+            self.draw()?;
+        }
+
+        Some(self.pending.drain(..).collect())
+    }
+
+    /// Draw the next number and mark it on all boards. The resulting events
+    /// are queued, so the boards always reflect the last drawn number.
+    fn draw(&mut self) -> Option<()> {
+        let number = self.moves.pop_front()?;
+        self.pending.push_back(Event::Drawn(number));
+
+        for (index, board) in self.boards.iter_mut().enumerate() {
+            if let Some((row, col)) = board.mark(number) {
+                self.pending.push_back(Event::Marked { board: index, row, col });
+
+                if board.is_completed() {
+                    let score = board.score(number);
+                    self.pending.push_back(Event::Won { board: index, score });
+                    self.results.push(Win { board: index, score });
+                }
+            }
+        }
+
+        Some(())
+    }
+}
+
+impl From<GameState> for Game {
+    fn from(state: GameState) -> Self {
+        let mut game = Game::new(
+            state.boards.into_iter().map(|b| b.into()).collect(),
+            state.draws.into(),
+        );
+        game.results = state.results;
+
+        game
+    }
+}
+
+impl Iterator for Game {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending.is_empty() {
+            self.draw()?;
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BOARD_SIZE;
+
+    fn board(offset: u32) -> Board {
+        let mut numbers = [[0; BOARD_SIZE]; BOARD_SIZE];
+        for (i, num) in numbers.iter_mut().flatten().enumerate() {
+            *num = offset + i as u32;
+        }
+
+        Board::from_numbers(numbers)
+    }
+
+    #[test]
+    fn test_events_are_in_order() {
+        let game = Game::new(vec![board(0), board(3)], [3, 0, 1, 2, 4].into());
+        let events = game.collect::<Vec<_>>();
+
+        assert_eq!(Event::Drawn(3), events[0]);
+        assert_eq!(Event::Marked { board: 0, row: 0, col: 3 }, events[1]);
+        assert_eq!(Event::Marked { board: 1, row: 0, col: 0 }, events[2]);
+
+        let wins = events.iter()
+            .filter(|e| matches!(e, Event::Won { .. }))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![&Event::Won { board: 0, score: 4 * (5..25).sum::<u32>() }], wins);
+    }
+
+    #[test]
+    fn test_resumed_game_matches_uninterrupted_game() {
+        let moves = [3, 0, 5, 1, 2, 4, 6, 7];
+        let full = Game::new(vec![board(0), board(5)], moves.into())
+            .collect::<Vec<_>>();
+
+        let mut game = Game::new(vec![board(0), board(5)], moves.into());
+        let mut resumed = game.next_draw().unwrap();
+        resumed.extend(game.next_draw().unwrap());
+
+        let mut game = Game::from(game.state());
+        resumed.extend(game.by_ref());
+
+        assert_eq!(full, resumed);
+        assert_eq!(1, game.results().len());
+    }
+}
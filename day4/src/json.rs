@@ -0,0 +1,210 @@
+use std::fmt::{self, Display};
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// The subset of JSON needed to store a game: no floats and no escapes in
+/// strings.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Number(i64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn parse(input: &str) -> Result<Value, String> {
+        let mut chars = input.chars().peekable();
+        let value = parse_value(&mut chars)?;
+
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => Ok(value),
+            Some(c) => Err(format!("Unexpected '{}' after the JSON value.", c)),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<&Value, String> {
+        match self {
+            Value::Object(fields) => fields.iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v)
+                .ok_or_else(|| format!("Missing field '{}'.", key)),
+            _ => Err(format!("Expected an object with field '{}'.", key)),
+        }
+    }
+
+    pub fn as_array(&self) -> Result<&[Value], String> {
+        match self {
+            Value::Array(values) => Ok(values),
+            _ => Err("Expected an array.".to_owned()),
+        }
+    }
+
+    pub fn as_bool(&self) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            _ => Err("Expected a boolean.".to_owned()),
+        }
+    }
+
+    pub fn as_u32(&self) -> Result<u32, String> {
+        match self {
+            Value::Number(n) => u32::try_from(*n).map_err(|_| format!("{} is out of range.", n)),
+            _ => Err("Expected a number.".to_owned()),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write!(f, "\"{}\"", s),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\"{}\":{}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), String> {
+    skip_whitespace(chars);
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        Some(c) => Err(format!("Expected '{}', found '{}'.", expected, c)),
+        None => Err(format!("Expected '{}', found the end of the input.", expected)),
+    }
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<Value, String> {
+    skip_whitespace(chars);
+
+    match chars.peek() {
+        Some('{') => parse_object(chars),
+        Some('[') => parse_array(chars),
+        Some('"') => parse_string(chars).map(Value::String),
+        Some('t') | Some('f') => {
+            let word = take_while(chars, |c| c.is_ascii_alphabetic());
+            match word.as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(format!("Unexpected literal '{}'.", word)),
+            }
+        }
+        Some(c) if *c == '-' || c.is_ascii_digit() => {
+            let number = take_while(chars, |c| c == '-' || c.is_ascii_digit());
+            number.parse::<i64>()
+                .map(Value::Number)
+                .map_err(|_| format!("Invalid number '{}'.", number))
+        }
+        Some(c) => Err(format!("Unexpected '{}'.", c)),
+        None => Err("Unexpected end of the input.".to_owned()),
+    }
+}
+
+fn take_while(chars: &mut Peekable<Chars>, predicate: impl Fn(char) -> bool) -> String {
+    let mut taken = String::new();
+    while let Some(c) = chars.next_if(|&c| predicate(c)) {
+        taken.push(c);
+    }
+
+    taken
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    expect(chars, '"')?;
+    let string = take_while(chars, |c| c != '"' && c != '\\');
+    expect(chars, '"')?;
+
+    Ok(string)
+}
+
+fn parse_array(chars: &mut Peekable<Chars>) -> Result<Value, String> {
+    expect(chars, '[')?;
+    let mut values = vec![];
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&']').is_some() {
+        return Ok(Value::Array(values));
+    }
+
+    loop {
+        values.push(parse_value(chars)?);
+
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some(']') => return Ok(Value::Array(values)),
+            _ => return Err("Expected ',' or ']' in array.".to_owned()),
+        }
+    }
+}
+
+fn parse_object(chars: &mut Peekable<Chars>) -> Result<Value, String> {
+    expect(chars, '{')?;
+    let mut fields = vec![];
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&'}').is_some() {
+        return Ok(Value::Object(fields));
+    }
+
+    loop {
+        skip_whitespace(chars);
+        let key = parse_string(chars)?;
+        expect(chars, ':')?;
+        fields.push((key, parse_value(chars)?));
+
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => return Ok(Value::Object(fields)),
+            _ => return Err("Expected ',' or '}' in object.".to_owned()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let input = r#"{ "draws": [1, 2, -3], "done": false, "name": "bingo", "empty": {} }"#;
+        let value = Value::parse(input).unwrap();
+
+        assert_eq!(Value::Bool(false), *value.get("done").unwrap());
+        assert_eq!(3, value.get("draws").unwrap().as_array().unwrap().len());
+        assert_eq!(value, Value::parse(&value.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_rejects_trailing_input() {
+        assert!(Value::parse("[1] 2").is_err());
+        assert!(Value::parse("[1,").is_err());
+    }
+}
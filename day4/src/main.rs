@@ -1,15 +1,123 @@
+mod analysis;
 mod board;
+mod game;
+mod json;
+mod replay;
+mod state;
 
 use std::collections::VecDeque;
+use std::env;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
+use std::time::Duration;
 use board::*;
+use game::*;
+use replay::Pace;
+use state::{GameState, Win};
+
+struct Options {
+    input: String,
+    replay: Option<Pace>,
+    analyse: bool,
+    board: Option<usize>,
+    load: Option<String>,
+    save: Option<(String, usize)>,
+}
+
+impl Options {
+    fn from_args() -> Self {
+        let mut options = Options {
+            input: "input.txt".to_owned(),
+            replay: None,
+            analyse: false,
+            board: None,
+            load: None,
+            save: None,
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--replay" => options.replay = Some(Pace::KeyPress),
+                "--interval" => {
+                    let millis = args.next()
+                        .and_then(|ms| ms.parse::<u64>().ok())
+                        .expect("--interval expects a number of milliseconds.");
+                    options.replay = Some(Pace::Interval(Duration::from_millis(millis)));
+                }
+                "--analyse" => options.analyse = true,
+                "--board" => {
+                    let board = args.next()
+                        .and_then(|b| b.parse::<usize>().ok())
+                        .expect("--board expects a board index.");
+                    options.board = Some(board);
+                }
+                "--load" => options.load = Some(args.next().expect("--load expects a path.")),
+                "--save" => {
+                    let path = args.next().expect("--save expects a path.");
+                    let after = args.next()
+                        .and_then(|n| n.parse::<usize>().ok())
+                        .expect("--save expects the number of draws after which to save.");
+                    options.save = Some((path, after));
+                }
+                _ => options.input = arg,
+            }
+        }
+
+        options
+    }
+}
 
 fn main() {
-    let lines = read_lines("input.txt");
-    
-    let mut moves = lines[0].split(',')
+    let options = Options::from_args();
+
+    let mut game = match options.load {
+        Some(ref path) => GameState::load(path)
+            .unwrap_or_else(|e| panic!("Failed to load {}: {}", path, e))
+            .into(),
+        None => read_game(&options.input),
+    };
+
+    if let Some(board) = options.board {
+        analyse_board(game.boards(), game.results(), board, &game.state().draws);
+        return;
+    }
+
+    if options.analyse {
+        analyse(game.boards(), game.results(), &game.state().draws);
+        return;
+    }
+
+    if let Some((path, after)) = options.save {
+        // Fewer numbers may be left than requested.
+        let played = (0..after)
+            .take_while(|_| game.next_draw().is_some())
+            .count();
+
+        game.state()
+            .save(&path)
+            .unwrap_or_else(|e| panic!("Failed to save {}: {}", path, e));
+        println!("Saved the game after {} draws to {}", played, path);
+        return;
+    }
+
+    if let Some(pace) = options.replay {
+        replay::replay(game, pace);
+        return;
+    }
+
+    game.by_ref().for_each(drop);
+
+    let results = game.results();
+    println!("First winner: {}", results.first().unwrap().score);
+    println!("Last winner: {}", results.last().unwrap().score);
+}
+
+fn read_game(filename: &str) -> Game {
+    let lines = read_lines(filename);
+
+    let moves = lines[0].split(',')
         .map(|num| num.parse::<u32>().expect("Failed to parse number."))
         .collect::<VecDeque<_>>();
 
@@ -20,20 +128,67 @@ fn main() {
         line += BOARD_SIZE + 1;
     }
 
-    let mut last_winner_score: Option<u32> = None;
-    while let Some(number) = moves.pop_front() {
-        for board in boards.iter_mut() {
-            if let Some(score) = board.try_complete(number) {
-                if last_winner_score.is_none() {
-                    println!("First winner: {}", score);
-                }
+    Game::new(boards, moves)
+}
 
-                last_winner_score = Some(score);
-            }
-        }
+/// Analyse the remaining draws. The boards of a loaded game keep their marks
+/// and the boards which have already won come first.
+fn analyse(boards: &[Board], results: &[Win], draws: &[u32]) {
+    let outcomes = analysis::outcomes(boards, draws);
+
+    for win in results {
+        println!("Board {} has already won with score {}", win.board, win.score);
+    }
+
+    for outcome in outcomes.iter() {
+        println!(
+            "Board {} wins on draw {} ({}) with score {}",
+            outcome.board, outcome.turn + 1, draws[outcome.turn], outcome.score
+        );
     }
 
-    println!("Last winner: {}", last_winner_score.unwrap());
+    let first = results.first().map(|win| win.score).or(outcomes.first().map(|o| o.score));
+    let last = outcomes.last().map(|o| o.score).or(results.last().map(|win| win.score));
+
+    if let (Some(first), Some(last)) = (first, last) {
+        println!("First winner: {}", first);
+        println!("Last winner: {}", last);
+    }
+}
+
+fn analyse_board(boards: &[Board], results: &[Win], board: usize, draws: &[u32]) {
+    if board >= boards.len() {
+        eprintln!("There is no board {}, there are only {} boards.", board, boards.len());
+        return;
+    }
+
+    let won = results.iter().find(|win| win.board == board);
+
+    match (won, analysis::winning_prefix(&boards[board], draws)) {
+        (Some(win), _) => println!(
+            "Board {} has already won with {:?} and score {}",
+            board, boards[board].winning_line().unwrap(), win.score
+        ),
+        (None, Some(prefix)) => println!(
+            "Board {} wins after {} draws with {:?}",
+            board, prefix.len(), analysis::winning_line(&boards[board], draws).unwrap()
+        ),
+        (None, None) => println!("Board {} never wins", board),
+    }
+
+    match (results.first(), analysis::winning_order(boards, board, draws)) {
+        (Some(first), _) if first.board == board => println!("Board {} has already won first", board),
+        (Some(first), _) => println!(
+            "No order of the draws makes board {} win first, board {} has already won",
+            board, first.board
+        ),
+        (None, Some(order)) => println!(
+            "Board {} wins first with the order: {}",
+            board,
+            order.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",")
+        ),
+        (None, None) => println!("No order of the draws makes board {} win first", board),
+    }
 }
 
 // The output is wrapped in a Result to allow matching on errors
@@ -47,7 +202,6 @@ where P: AsRef<Path>, {
 
     io::BufReader::new(file)
         .lines()
-        .into_iter()
         .map(|l| l.expect("Failed to read line."))
         .collect()
-}
\ No newline at end of file
+}
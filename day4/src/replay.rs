@@ -0,0 +1,107 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::{Board, BOARD_SIZE};
+use crate::game::{Event, Game};
+
+const BOARDS_PER_ROW: usize = 6;
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+const HIGHLIGHT: &str = "\x1b[1;32m";
+const RESET: &str = "\x1b[0m";
+
+/// How the replay advances from one draw to the next.
+#[derive(Clone, Copy, Debug)]
+pub enum Pace {
+    KeyPress,
+    Interval(Duration),
+}
+
+/// Play the game, rendering all boards in the terminal after each draw.
+pub fn replay(mut game: Game, pace: Pace) {
+    let stdin = io::stdin();
+    let mut winners = vec![];
+
+    while let Some(events) = game.next_draw() {
+        let mut drawn = None;
+        for event in events.iter() {
+            match *event {
+                Event::Drawn(number) => drawn = Some(number),
+                Event::Won { board, score } => winners.push((board, score)),
+                Event::Marked { .. } => {}
+            }
+        }
+
+        print!("{}", CLEAR_SCREEN);
+        if let Some(number) = drawn {
+            println!("Drawn: {}", number);
+        }
+        print!("{}", render(game.boards()));
+        for (board, score) in winners.iter() {
+            println!("Board {} won with score {}", board, score);
+        }
+
+        match pace {
+            Pace::KeyPress => {
+                print!("Press enter for the next draw...");
+                io::stdout().flush().expect("Failed to flush stdout.");
+
+                let mut line = String::new();
+                if stdin.lock().read_line(&mut line).expect("Failed to read stdin.") == 0 {
+                    return;
+                }
+            }
+            Pace::Interval(interval) => thread::sleep(interval),
+        }
+    }
+}
+
+/// Render the boards side by side, in the same style as their `Debug` output.
+/// The winning line of completed boards is highlighted.
+pub fn render(boards: &[Board]) -> String {
+    let mut output = String::new();
+
+    for (chunk_index, chunk) in boards.chunks(BOARDS_PER_ROW).enumerate() {
+        for (i, board) in chunk.iter().enumerate() {
+            let index = chunk_index * BOARDS_PER_ROW + i;
+            let status = if board.is_completed() { "won" } else { "" };
+            write!(output, "{:<26}", format!("#{} {}", index, status)).unwrap();
+        }
+        output.push('\n');
+
+        for row in 0..BOARD_SIZE {
+            for board in chunk {
+                for col in 0..BOARD_SIZE {
+                    render_cell(&mut output, board, row, col);
+                }
+                output.push(' ');
+            }
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn render_cell(output: &mut String, board: &Board, row: usize, col: usize) {
+    let number = board.number_at(row, col);
+    let highlighted = board.winning_line()
+        .is_some_and(|line| line.contains(row, col));
+
+    if highlighted {
+        output.push_str(HIGHLIGHT);
+    }
+
+    if board.is_marked(row, col) {
+        write!(output, "|{:02}|", number).unwrap();
+    } else {
+        write!(output, " {:02} ", number).unwrap();
+    }
+
+    if highlighted {
+        output.push_str(RESET);
+    }
+    output.push(' ');
+}
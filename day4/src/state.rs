@@ -0,0 +1,350 @@
+use std::fs;
+use std::path::Path;
+
+use crate::board::{Board, BOARD_SIZE};
+use crate::json::Value;
+
+/// A board which has won, with the score it won with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Win {
+    pub board: usize,
+    pub score: u32,
+}
+
+/// Everything needed to resume a game: the boards with their marks, the
+/// numbers which are still to be drawn and the boards which have won so far.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GameState {
+    pub boards: Vec<BoardState>,
+    pub draws: Vec<u32>,
+    pub results: Vec<Win>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoardState {
+    pub numbers: [[u32; BOARD_SIZE]; BOARD_SIZE],
+    pub marked: [[bool; BOARD_SIZE]; BOARD_SIZE],
+}
+
+impl BoardState {
+    /// Whether a row or column is fully marked.
+    fn is_completed(&self) -> bool {
+        (0..BOARD_SIZE).any(|i| (0..BOARD_SIZE).all(|j| self.marked[i][j]) ||
+            (0..BOARD_SIZE).all(|j| self.marked[j][i]))
+    }
+
+    fn has_duplicates(&self) -> bool {
+        let mut numbers = self.numbers.iter().flatten().collect::<Vec<_>>();
+        numbers.sort_unstable();
+
+        numbers.windows(2).any(|pair| pair[0] == pair[1])
+    }
+}
+
+impl From<&Board> for BoardState {
+    fn from(board: &Board) -> Self {
+        BoardState {
+            numbers: board.grid(),
+            marked: board.marks(),
+        }
+    }
+}
+
+/// Panics on duplicate numbers, which states loaded by `GameState` never
+/// contain.
+impl From<BoardState> for Board {
+    fn from(state: BoardState) -> Self {
+        Board::restore(state.numbers, state.marked)
+    }
+}
+
+impl GameState {
+    /// Save the state to the given file. Files ending in `.json` are written
+    /// as JSON, all other files use the text format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let contents = if is_json(path.as_ref()) {
+            self.to_json()
+        } else {
+            self.to_text()
+        };
+
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    /// Load the state from the given file, using the same format selection as
+    /// `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let contents = fs::read_to_string(path.as_ref()).map_err(|e| e.to_string())?;
+
+        if is_json(path.as_ref()) {
+            Self::from_json(&contents)
+        } else {
+            Self::from_text(&contents)
+        }
+    }
+
+    /// The text format mirrors the puzzle input: a line with the remaining
+    /// draws, a line with the results as `board:score` pairs, followed by the
+    /// boards where marked numbers are written as `|NN|`.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "draws {}\nresults {}\n",
+            join(self.draws.iter(), ","),
+            join(self.results.iter().map(|w| format!("{}:{}", w.board, w.score)), " ")
+        );
+
+        for board in self.boards.iter() {
+            text.push('\n');
+            for row in 0..BOARD_SIZE {
+                let line = (0..BOARD_SIZE)
+                    .map(|col| if board.marked[row][col] {
+                        format!("|{:02}|", board.numbers[row][col])
+                    } else {
+                        format!(" {:02} ", board.numbers[row][col])
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                text.push_str(line.trim_end());
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+
+        let draws = lines.next()
+            .and_then(|l| l.strip_prefix("draws"))
+            .ok_or("Expected a line with the draws.")?
+            .split(',')
+            .map(str::trim)
+            .filter(|num| !num.is_empty())
+            .map(parse_number)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let results = lines.next()
+            .and_then(|l| l.strip_prefix("results"))
+            .ok_or("Expected a line with the results.")?
+            .split_whitespace()
+            .map(|result| {
+                let (board, score) = result.split_once(':')
+                    .ok_or_else(|| format!("Invalid result '{}'.", result))?;
+                Ok(Win {
+                    board: parse_number(board)? as usize,
+                    score: parse_number(score)?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let board_lines = lines.filter(|l| !l.trim().is_empty()).collect::<Vec<_>>();
+        if board_lines.len() % BOARD_SIZE != 0 {
+            return Err("Incomplete board in the state.".to_owned());
+        }
+
+        let boards = board_lines.chunks(BOARD_SIZE)
+            .map(|rows| {
+                let mut board = BoardState {
+                    numbers: [[0; BOARD_SIZE]; BOARD_SIZE],
+                    marked: [[false; BOARD_SIZE]; BOARD_SIZE],
+                };
+
+                for (row, line) in rows.iter().enumerate() {
+                    let cells = line.split_whitespace().collect::<Vec<_>>();
+                    if cells.len() != BOARD_SIZE {
+                        return Err(format!("Invalid board row '{}'.", line));
+                    }
+
+                    for (col, cell) in cells.into_iter().enumerate() {
+                        let number = cell.strip_prefix('|').and_then(|c| c.strip_suffix('|'));
+                        board.marked[row][col] = number.is_some();
+                        board.numbers[row][col] = parse_number(number.unwrap_or(cell))?;
+                    }
+                }
+
+                Ok(board)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Self::validated(boards, draws, results)
+    }
+
+    pub fn to_json(&self) -> String {
+        let numbers = |n: &[u32]| Value::Array(n.iter().map(|&n| Value::Number(n as i64)).collect());
+
+        let boards = self.boards.iter()
+            .map(|board| Value::Object(vec![
+                ("numbers".to_owned(), Value::Array(board.numbers.iter().map(|r| numbers(r)).collect())),
+                ("marked".to_owned(), Value::Array(board.marked.iter()
+                    .map(|r| Value::Array(r.iter().map(|&m| Value::Bool(m)).collect()))
+                    .collect())),
+            ]))
+            .collect();
+
+        let results = self.results.iter()
+            .map(|win| Value::Object(vec![
+                ("board".to_owned(), Value::Number(win.board as i64)),
+                ("score".to_owned(), Value::Number(win.score as i64)),
+            ]))
+            .collect();
+
+        Value::Object(vec![
+            ("draws".to_owned(), numbers(&self.draws)),
+            ("results".to_owned(), Value::Array(results)),
+            ("boards".to_owned(), Value::Array(boards)),
+        ]).to_string()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value = Value::parse(json)?;
+
+        let draws = value.get("draws")?.as_array()?.iter()
+            .map(Value::as_u32)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let results = value.get("results")?.as_array()?.iter()
+            .map(|win| Ok(Win {
+                board: win.get("board")?.as_u32()? as usize,
+                score: win.get("score")?.as_u32()?,
+            }))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let boards = value.get("boards")?.as_array()?.iter()
+            .map(|board| Ok(BoardState {
+                numbers: grid(board.get("numbers")?, Value::as_u32)?,
+                marked: grid(board.get("marked")?, Value::as_bool)?,
+            }))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Self::validated(boards, draws, results)
+    }
+
+    /// Check that the state can come from a game: every board has distinct
+    /// numbers, and exactly the boards with a complete row or column have
+    /// won, each once.
+    fn validated(boards: Vec<BoardState>, draws: Vec<u32>, results: Vec<Win>) -> Result<Self, String> {
+        if let Some(win) = results.iter().find(|win| win.board >= boards.len()) {
+            return Err(format!("Result for board {}, but there are {} boards.", win.board, boards.len()));
+        }
+
+        if let Some(index) = boards.iter().position(BoardState::has_duplicates) {
+            return Err(format!("Board {} contains duplicate numbers.", index));
+        }
+
+        for (index, board) in boards.iter().enumerate() {
+            let wins = results.iter().filter(|win| win.board == index).count();
+
+            match (board.is_completed(), wins) {
+                (true, 0) => return Err(format!("Board {} is complete, but has not won.", index)),
+                (false, 1..) => return Err(format!("Board {} has won, but has no complete row or column.", index)),
+                (_, 2..) => return Err(format!("Board {} has won more than once.", index)),
+                _ => {},
+            }
+        }
+
+        Ok(GameState { boards, draws, results })
+    }
+}
+
+fn grid<T: Copy + Default>(
+    value: &Value,
+    element: impl Fn(&Value) -> Result<T, String>,
+) -> Result<[[T; BOARD_SIZE]; BOARD_SIZE], String> {
+    let rows = value.as_array()?;
+    if rows.len() != BOARD_SIZE {
+        return Err(format!("Expected {} rows, found {}.", BOARD_SIZE, rows.len()));
+    }
+
+    let mut grid = [[T::default(); BOARD_SIZE]; BOARD_SIZE];
+    for (row, values) in rows.iter().enumerate() {
+        let values = values.as_array()?;
+        if values.len() != BOARD_SIZE {
+            return Err(format!("Expected {} columns, found {}.", BOARD_SIZE, values.len()));
+        }
+
+        for (col, value) in values.iter().enumerate() {
+            grid[row][col] = element(value)?;
+        }
+    }
+
+    Ok(grid)
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+fn parse_number(num: &str) -> Result<u32, String> {
+    num.trim().parse::<u32>().map_err(|_| format!("Invalid number '{}'.", num))
+}
+
+fn join<T: ToString>(items: impl Iterator<Item = T>, separator: &str) -> String {
+    items.map(|i| i.to_string()).collect::<Vec<_>>().join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> GameState {
+        let mut numbers = [[0; BOARD_SIZE]; BOARD_SIZE];
+        for (i, num) in numbers.iter_mut().flatten().enumerate() {
+            *num = i as u32 * 3;
+        }
+
+        let mut marked = [[false; BOARD_SIZE]; BOARD_SIZE];
+        marked[0] = [true; BOARD_SIZE];
+        marked[3][1] = true;
+
+        GameState {
+            boards: vec![BoardState { numbers, marked }, BoardState { numbers, marked: Default::default() }],
+            draws: vec![7, 12, 1],
+            results: vec![Win { board: 0, score: 1234 }],
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let state = state();
+        assert_eq!(state, GameState::from_text(&state.to_text()).unwrap());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let state = state();
+        assert_eq!(state, GameState::from_json(&state.to_json()).unwrap());
+    }
+
+    #[test]
+    fn test_rejects_unknown_board() {
+        let mut state = state();
+        state.results.push(Win { board: 2, score: 1 });
+
+        assert!(GameState::from_json(&state.to_json()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_duplicate_numbers() {
+        let mut state = state();
+        state.boards[1].numbers[4][4] = state.boards[1].numbers[0][0];
+
+        assert_eq!(Err("Board 1 contains duplicate numbers.".to_owned()), GameState::from_text(&state.to_text()));
+        assert!(GameState::from_json(&state.to_json()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_marks_inconsistent_with_results() {
+        let mut completed = state();
+        completed.boards[1].marked[2] = [true; BOARD_SIZE];
+        assert!(GameState::from_text(&completed.to_text()).is_err());
+
+        let mut incomplete = state();
+        incomplete.boards[0].marked[0][3] = false;
+        assert!(GameState::from_json(&incomplete.to_json()).is_err());
+
+        let mut won_twice = state();
+        won_twice.results.push(Win { board: 0, score: 1 });
+        assert!(GameState::from_json(&won_twice.to_json()).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use crate::line::{Line, Point};
+
+/// The smallest axis-aligned box containing a set of points. Both corners are
+/// inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(a: Point, b: Point) -> Self {
+        BoundingBox {
+            min: Point { x: i64::min(a.x, b.x), y: i64::min(a.y, b.y), z: i64::min(a.z, b.z) },
+            max: Point { x: i64::max(a.x, b.x), y: i64::max(a.y, b.y), z: i64::max(a.z, b.z) },
+        }
+    }
+
+    /// The bounding box of all given lines, None if there are no lines.
+    pub fn of_lines<'a>(lines: impl IntoIterator<Item = &'a Line>) -> Option<Self> {
+        lines.into_iter()
+            .map(Line::bounding_box)
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let min = BoundingBox::new(self.min, other.min).min;
+        let max = BoundingBox::new(self.max, other.max).max;
+
+        BoundingBox { min, max }
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) &&
+        (self.min.y..=self.max.y).contains(&point.y) &&
+        (self.min.z..=self.max.z).contains(&point.z)
+    }
+
+    pub fn width(&self) -> u64 {
+        self.max.x.abs_diff(self.min.x) + 1
+    }
+
+    pub fn height(&self) -> u64 {
+        self.max.y.abs_diff(self.min.y) + 1
+    }
+
+    pub fn depth(&self) -> u64 {
+        self.max.z.abs_diff(self.min.z) + 1
+    }
+
+    /// Whether all points in this box have the same z coordinate.
+    pub fn is_planar(&self) -> bool {
+        self.depth() == 1
+    }
+
+    /// The number of points in this box, which may not fit in a u64.
+    pub fn volume(&self) -> u128 {
+        self.width() as u128 * self.height() as u128 * self.depth() as u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounding_box_of_lines() {
+        let lines = [Line::from("8,0 -> 0,8"), Line::from("9,4 -> 3,4"), Line::from("2,2 -> 2,10")];
+        let bounding_box = BoundingBox::of_lines(&lines).unwrap();
+
+        assert_eq!(Point { x: 0, y: 0, z: 0 }, bounding_box.min);
+        assert_eq!(Point { x: 9, y: 10, z: 0 }, bounding_box.max);
+        assert_eq!(10, bounding_box.width());
+        assert!(bounding_box.contains((9, 10).into()));
+        assert!(!bounding_box.contains((10, 9).into()));
+        assert!(!bounding_box.contains((9, 10, 1).into()));
+        assert!(bounding_box.is_planar());
+    }
+
+    #[test]
+    fn test_bounding_box_in_space() {
+        let lines = [Line::from("8,0,-2 -> 0,8,2"), Line::from("9,4 -> 3,4")];
+        let bounding_box = BoundingBox::of_lines(&lines).unwrap();
+
+        assert_eq!(5, bounding_box.depth());
+        assert_eq!(10 * 9 * 5, bounding_box.volume());
+        assert!(bounding_box.contains((1, 1, -1).into()));
+    }
+}
@@ -0,0 +1,455 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+
+use crate::line::{Line, Point};
+
+/// All arithmetic is done exactly on i128, which is large enough as long as
+/// no coordinate exceeds this magnitude. Only lines in the plane z = 0 are
+/// supported.
+pub const MAX_COORDINATE: i64 = 1 << 23;
+
+/// Why the intersections of some lines can't be found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntersectionError {
+    /// The coordinate is too large for exact arithmetic.
+    CoordinateTooLarge(i64),
+    /// The line with this index leaves the plane z = 0.
+    NotPlanar(usize),
+}
+
+impl Display for IntersectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntersectionError::CoordinateTooLarge(c) => write!(
+                f, "coordinate {} is too large, intersections are limited to coordinates up to {} in magnitude",
+                c, MAX_COORDINATE
+            ),
+            IntersectionError::NotPlanar(index) => write!(
+                f, "line {} leaves the plane z = 0, only planar lines can be intersected", index + 1
+            ),
+        }
+    }
+}
+
+/// A point with rational coordinates `(x / denominator, y / denominator)`,
+/// as two lines can cross in between lattice points. The representation is
+/// normalised, so equal points compare equal.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct RationalPoint {
+    x: i128,
+    y: i128,
+    denominator: i128,
+}
+
+impl RationalPoint {
+    fn new(x: i128, y: i128, denominator: i128) -> Self {
+        let sign = denominator.signum();
+        let divisor = gcd(gcd(x.abs(), y.abs()), denominator.abs());
+
+        RationalPoint {
+            x: sign * x / divisor,
+            y: sign * y / divisor,
+            denominator: sign * denominator / divisor,
+        }
+    }
+}
+
+impl From<Point> for RationalPoint {
+    fn from(point: Point) -> Self {
+        RationalPoint { x: point.x as i128, y: point.y as i128, denominator: 1 }
+    }
+}
+
+impl Ord for RationalPoint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.x * other.denominator).cmp(&(other.x * self.denominator))
+            .then_with(|| (self.y * other.denominator).cmp(&(other.y * self.denominator)))
+    }
+}
+
+impl PartialOrd for RationalPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for RationalPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let component = |value: i128| {
+            let divisor = gcd(value.abs(), self.denominator);
+            if divisor == self.denominator {
+                format!("{}", value / divisor)
+            } else {
+                format!("{}/{}", value / divisor, self.denominator / divisor)
+            }
+        };
+
+        write!(f, "{},{}", component(self.x), component(self.y))
+    }
+}
+
+/// Where two lines meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Intersection {
+    Point(RationalPoint),
+    /// The lines are collinear and share the segment between these points.
+    Overlap(Point, Point),
+}
+
+/// Two lines, identified by their index, and where they meet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crossing {
+    pub lines: (usize, usize),
+    pub intersection: Intersection,
+}
+
+/// A point where at least two lines meet. Its multiplicity is the number of
+/// lines through it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Junction {
+    pub point: RationalPoint,
+    pub lines: Vec<usize>,
+}
+
+impl Junction {
+    pub fn multiplicity(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Intersections {
+    /// Every intersecting pair of lines, ordered by their indices.
+    pub crossings: Vec<Crossing>,
+    /// The points at which lines cross or touch, in sweep order. Points in the
+    /// middle of a collinear overlap are only included if a third line
+    /// passes through them.
+    pub junctions: Vec<Junction>,
+}
+
+/// Where the two lines meet, None if they don't. Both lines have to lie in
+/// the plane z = 0, which `find_intersections` checks up front.
+fn intersect(a: &Line, b: &Line) -> Option<Intersection> {
+    debug_assert!(is_planar(a) && is_planar(b), "Only lines in the plane z = 0 can be intersected.");
+
+    let (p, r) = (a.start, offset(a.start, a.end));
+    let (q, s) = (b.start, offset(b.start, b.end));
+    let qp = offset(p, q);
+    let denominator = cross(r, s);
+
+    if r == (0, 0) && s == (0, 0) {
+        return (p == q).then(|| Intersection::Point(p.into()));
+    }
+
+    if denominator == 0 {
+        if cross(qp, r) != 0 || cross(qp, s) != 0 {
+            return None;
+        }
+
+        return collinear_overlap(a, b);
+    }
+
+    // Solve p + t * r = q + u * s, with t and u as fractions of the denominator.
+    let (t, u) = (cross(qp, s), cross(qp, r));
+    let range = if denominator > 0 { 0..=denominator } else { denominator..=0 };
+    if !range.contains(&t) || !range.contains(&u) {
+        return None;
+    }
+
+    Some(Intersection::Point(RationalPoint::new(
+        p.x as i128 * denominator + t * r.0,
+        p.y as i128 * denominator + t * r.1,
+        denominator,
+    )))
+}
+
+/// The shared part of two lines on the same infinite line.
+fn collinear_overlap(a: &Line, b: &Line) -> Option<Intersection> {
+    let direction = if a.start != a.end { offset(a.start, a.end) } else { offset(b.start, b.end) };
+    let position = |point: Point| direction.0 * point.x as i128 + direction.1 * point.y as i128;
+
+    let (a_min, a_max) = min_max_by_key(a.start, a.end, position);
+    let (b_min, b_max) = min_max_by_key(b.start, b.end, position);
+
+    let low = if position(a_min) >= position(b_min) { a_min } else { b_min };
+    let high = if position(a_max) <= position(b_max) { a_max } else { b_max };
+
+    match position(low).cmp(&position(high)) {
+        Ordering::Greater => None,
+        Ordering::Equal => Some(Intersection::Point(low.into())),
+        Ordering::Less => Some(Intersection::Overlap(low, high)),
+    }
+}
+
+fn min_max_by_key(a: Point, b: Point, key: impl Fn(Point) -> i128) -> (Point, Point) {
+    if key(a) <= key(b) { (a, b) } else { (b, a) }
+}
+
+/// A line oriented such that `left` comes before `right` in sweep order.
+struct Segment {
+    left: Point,
+    right: Point,
+}
+
+impl Segment {
+    fn new(line: &Line) -> Self {
+        let (left, right) = if (line.start.x, line.start.y) <= (line.end.x, line.end.y) {
+            (line.start, line.end)
+        } else {
+            (line.end, line.start)
+        };
+
+        Segment { left, right }
+    }
+
+    fn is_vertical(&self) -> bool {
+        self.left.x == self.right.x
+    }
+
+    /// Compare the height of this segment on the sweep line to the height of
+    /// the event point. Vertical segments lie on the sweep line, and are only
+    /// in the status while they contain the event point.
+    fn height_cmp(&self, event: &RationalPoint) -> Ordering {
+        if self.is_vertical() {
+            return Ordering::Equal;
+        }
+
+        let (dx, dy) = offset(self.left, self.right);
+        let height = self.left.y as i128 * dx * event.denominator +
+            (event.x - self.left.x as i128 * event.denominator) * dy;
+
+        height.cmp(&(event.y * dx))
+    }
+
+    /// The order of segments through the same point just after the sweep line
+    /// passed it. Vertical segments come last.
+    fn slope_cmp(&self, other: &Segment) -> Ordering {
+        match (self.is_vertical(), other.is_vertical()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => {
+                let (dx, dy) = offset(self.left, self.right);
+                let (other_dx, other_dy) = offset(other.left, other.right);
+
+                (dy * other_dx).cmp(&(other_dy * dx))
+            }
+        }
+    }
+}
+
+/// Find all intersections between the lines with a Bentley-Ottmann sweep: a
+/// vertical sweep line passes over the plane, keeping the lines it crosses
+/// ordered by height. Lines can only intersect after being neighbours in this
+/// order, so only neighbours are ever tested against each other.
+///
+/// The status is kept in a vector, so every event costs time linear in the
+/// number of lines on the sweep line for the insertions and removals. That
+/// makes the worst case quadratic when most lines are crossed at once, but
+/// the binary searches keep the comparisons logarithmic, and for vents the
+/// status stays small.
+pub fn find_intersections(lines: &[Line]) -> Result<Intersections, IntersectionError> {
+    if let Some(index) = lines.iter().position(|l| !is_planar(l)) {
+        return Err(IntersectionError::NotPlanar(index));
+    }
+
+    if let Some(c) = lines.iter()
+        .flat_map(|l| [l.start.x, l.start.y, l.end.x, l.end.y])
+        .find(|c| c.unsigned_abs() > MAX_COORDINATE as u64) {
+        return Err(IntersectionError::CoordinateTooLarge(c));
+    }
+
+    let segments = lines.iter().map(Segment::new).collect::<Vec<_>>();
+
+    let mut events: BTreeMap<RationalPoint, Vec<usize>> = BTreeMap::new();
+    for (index, segment) in segments.iter().enumerate() {
+        events.entry(segment.left.into()).or_default().push(index);
+        events.entry(segment.right.into()).or_default();
+    }
+
+    let mut status: Vec<usize> = vec![];
+    let mut junctions = vec![];
+    let mut crossings = vec![];
+    let mut collinear = BTreeSet::new();
+
+    while let Some((event, starting)) = events.pop_first() {
+        let low = status.partition_point(|&s| segments[s].height_cmp(&event) == Ordering::Less);
+        let high = status.partition_point(|&s| segments[s].height_cmp(&event) != Ordering::Greater);
+        let containing = status.drain(low..high).collect::<Vec<_>>();
+
+        let mut involved = starting.iter().chain(containing.iter()).copied().collect::<Vec<_>>();
+        if involved.len() >= 2 {
+            involved.sort_unstable();
+
+            for (i, &a) in involved.iter().enumerate() {
+                for &b in involved[i + 1..].iter() {
+                    if cross(offset(lines[a].start, lines[a].end), offset(lines[b].start, lines[b].end)) == 0 {
+                        collinear.insert((a, b));
+                    } else {
+                        crossings.push(Crossing { lines: (a, b), intersection: Intersection::Point(event) });
+                    }
+                }
+            }
+
+            junctions.push(Junction { point: event, lines: involved });
+        }
+
+        // Single points start and end here, so never enter the status.
+        let mut continuing = starting.into_iter()
+            .filter(|&s| segments[s].left != segments[s].right)
+            .chain(containing.into_iter().filter(|&s| RationalPoint::from(segments[s].right) != event))
+            .collect::<Vec<_>>();
+        continuing.sort_by(|&a, &b| segments[a].slope_cmp(&segments[b]).then(a.cmp(&b)));
+
+        let inserted = continuing.len();
+        status.splice(low..low, continuing);
+
+        if low > 0 && low < status.len() {
+            check_neighbours(&mut events, lines, status[low - 1], status[low], &event);
+        }
+
+        let last = low + inserted;
+        if inserted > 0 && last < status.len() {
+            check_neighbours(&mut events, lines, status[last - 1], status[last], &event);
+        }
+    }
+
+    crossings.extend(collinear.into_iter()
+        .filter_map(|(a, b)| intersect(&lines[a], &lines[b])
+            .map(|intersection| Crossing { lines: (a, b), intersection })));
+    crossings.sort_by_key(|c| c.lines);
+
+    Ok(Intersections { crossings, junctions })
+}
+
+/// Schedule the point where the neighbouring lines cross, if the sweep line
+/// still has to pass it. Collinear lines meet at an endpoint, which is always
+/// scheduled already.
+fn check_neighbours(
+    events: &mut BTreeMap<RationalPoint, Vec<usize>>,
+    lines: &[Line],
+    a: usize,
+    b: usize,
+    event: &RationalPoint,
+) {
+    if let Some(Intersection::Point(point)) = intersect(&lines[a], &lines[b]) {
+        if point > *event {
+            events.entry(point).or_default();
+        }
+    }
+}
+
+fn is_planar(line: &Line) -> bool {
+    line.start.z == 0 && line.end.z == 0
+}
+
+fn offset(from: Point, to: Point) -> (i128, i128) {
+    (to.x as i128 - from.x as i128, to.y as i128 - from.y as i128)
+}
+
+fn cross(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(lines: &[Line]) -> Vec<Crossing> {
+        let mut crossings = vec![];
+        for a in 0..lines.len() {
+            for b in a + 1..lines.len() {
+                if let Some(intersection) = intersect(&lines[a], &lines[b]) {
+                    crossings.push(Crossing { lines: (a, b), intersection });
+                }
+            }
+        }
+
+        crossings
+    }
+
+    #[test]
+    fn test_intersect() {
+        let a = Line::from("0,0 -> 4,4");
+
+        assert_eq!(
+            Some(Intersection::Point(RationalPoint::new(1, 1, 2))),
+            intersect(&a, &Line::from("0,1 -> 1,0"))
+        );
+        assert_eq!(
+            Some(Intersection::Overlap((2, 2).into(), (4, 4).into())),
+            intersect(&a, &Line::from("6,6 -> 2,2"))
+        );
+        assert_eq!(
+            Some(Intersection::Point(RationalPoint::from(Point::from((4, 4))))),
+            intersect(&a, &Line::from("4,4 -> 5,5"))
+        );
+        assert_eq!(None, intersect(&a, &Line::from("1,0 -> 5,4")));
+        assert_eq!(None, intersect(&a, &Line::from("3,0 -> 3,2")));
+    }
+
+    #[test]
+    fn test_junction_multiplicity() {
+        let lines = crate::line::example();
+        let intersections = find_intersections(&lines).unwrap();
+
+        let at = |x, y| intersections.junctions.iter()
+            .find(|j| j.point == RationalPoint::from(Point::from((x, y))))
+            .map(Junction::multiplicity);
+
+        assert_eq!(Some(3), at(4, 4));
+        assert_eq!(Some(2), at(7, 4));
+        assert_eq!(None, at(8, 8));
+        assert_eq!(brute_force(&lines), intersections.crossings);
+    }
+
+    #[test]
+    fn test_sweep_matches_brute_force() {
+        let mut seed = 42u64;
+        let mut random = |range: i64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) as i64 % range
+        };
+
+        for _ in 0..200 {
+            let lines = (0..25)
+                .map(|_| {
+                    let start = Point::from((random(12), random(12)));
+                    let end = match random(4) {
+                        0 => Point::from((start.x, random(12))),
+                        1 => Point::from((random(12), start.y)),
+                        _ => Point::from((random(12), random(12))),
+                    };
+
+                    Line { start, end }
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(brute_force(&lines), find_intersections(&lines).unwrap().crossings);
+        }
+    }
+
+    #[test]
+    fn test_large_coordinates_are_reported() {
+        let lines = [Line::from("0,0 -> 8388608,8388608"), Line::from("0,8388608 -> 8388608,0")];
+        assert_eq!(Ok(1), find_intersections(&lines).map(|i| i.crossings.len()));
+
+        let lines = [Line::from("0,0 -> 9000000,9000000"), Line::from("0,9000000 -> 9000000,0")];
+        assert_eq!(
+            Err(IntersectionError::CoordinateTooLarge(9000000)),
+            find_intersections(&lines).map(|i| i.crossings.len())
+        );
+    }
+
+    #[test]
+    fn test_lines_in_space_are_reported() {
+        let lines = [Line::from("0,0 -> 4,4"), Line::from("0,4,0 -> 4,0,2")];
+
+        assert_eq!(Err(IntersectionError::NotPlanar(1)), find_intersections(&lines).map(|i| i.crossings.len()));
+    }
+}
@@ -1,79 +1,344 @@
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+use std::fmt::{self, Display};
+
+use crate::bounding_box::BoundingBox;
+
+/// A point in space. Points in the plane have z = 0.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
 pub struct Point {
-    pub x: u32,
-    pub y: u32,
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
 }
 
 impl Point {
+    /// Whether the line to the other point moves along at least two axes,
+    /// by the same distance along each of them.
     pub fn is_at_45_deg(&self, other: Point) -> bool {
-        let (dx, dy) = self.delta(other);
-        
-        dx == dy
+        let deltas = self.delta(other).into_iter().filter(|&d| d != 0).collect::<Vec<_>>();
+
+        deltas.len() >= 2 && deltas.iter().all(|&d| d == deltas[0])
     }
 
-    pub fn delta(&self, other: Point) -> (u32, u32) {
-        let dx = u32::abs_diff(self.x, other.x);
-        let dy = u32::abs_diff(self.y, other.y);
+    pub fn delta(&self, other: Point) -> [u64; 3] {
+        let [x, y, z] = self.coordinates();
+        let [other_x, other_y, other_z] = other.coordinates();
+
+        [x.abs_diff(other_x), y.abs_diff(other_y), z.abs_diff(other_z)]
+    }
 
-        (dx, dy)
+    pub fn coordinates(&self) -> [i64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl From<[i64; 3]> for Point {
+    fn from([x, y, z]: [i64; 3]) -> Self {
+        Point { x, y, z }
     }
 }
 
 impl From<&str> for Point {
     fn from(input: &str) -> Self {
         let components = input.split(',')
-            .map(|num| num.parse::<u32>().expect("Failed to parse number."))
+            .map(|num| num.trim().parse::<i64>().expect("Failed to parse number."))
             .collect::<Vec<_>>();
 
-        assert!(components.len() == 2, "A point is defined by 2 components.");
+        assert!(
+            components.len() == 2 || components.len() == 3,
+            "A point is defined by 2 or 3 components."
+        );
 
         Point {
             x: components[0],
             y: components[1],
+            z: components.get(2).copied().unwrap_or(0),
         }
     }
 }
 
-impl From<(u32, u32)> for Point {
-    fn from((x, y): (u32, u32)) -> Self {
-        Point { x, y }
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Self {
+        Point { x, y, z: 0 }
     }
 }
 
+impl From<(i64, i64, i64)> for Point {
+    fn from((x, y, z): (i64, i64, i64)) -> Self {
+        Point { x, y, z }
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.z == 0 {
+            write!(f, "{},{}", self.x, self.y)
+        } else {
+            write!(f, "{},{},{}", self.x, self.y, self.z)
+        }
+    }
+}
+
+/// The orientation of a line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// A single point, which goes nowhere.
+    Point,
+    /// Along the x axis.
+    Horizontal,
+    /// Along the y axis.
+    Vertical,
+    /// Along the z axis.
+    Depth,
+    /// At 45 degrees to every axis it moves along.
+    Diagonal,
+    Other,
+}
+
 #[derive(Hash)]
 pub struct Line {
     pub start: Point,
     pub end: Point,
 }
 
+/// How the points covered by a line are determined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rasterisation {
+    /// Only the integer points which lie exactly on the line.
+    #[default]
+    Exact,
+    /// The points closest to the line, one for every step along its major
+    /// axis, as drawn by Bresenham's algorithm.
+    Bresenham,
+}
+
 impl Line {
+    /// Whether the point lies exactly on this line.
     pub fn covers(&self, point: Point) -> bool {
-        let (start_dx, start_dy) = self.start.delta(point);
-        let (end_dx, end_dy) = self.end.delta(point);
-        let (own_dx, own_dy) = self.start.delta(self.end);
-
-        if own_dx == 0 && start_dx == 0 {
-            let min_y = u32::min(self.start.y, self.end.y);
-            let max_y = u32::max(self.start.y, self.end.y);
-
-            min_y <= point.y && point.y <= max_y
-        } else if own_dy == 0 && start_dy == 0 {
-            let min_x = u32::min(self.start.x, self.end.x);
-            let max_x = u32::max(self.start.x, self.end.x);
-
-            min_x <= point.x && point.x <= max_x
-        } else if self.is_diagonal() && self.start.is_at_45_deg(point) {
-            start_dx + end_dx == own_dx && start_dy + end_dy == own_dy
-        } else {
-            false
+        let (step, steps) = self.step();
+        let start = self.start.coordinates();
+        let offset = [0, 1, 2].map(|axis| point.coordinates()[axis] - start[axis]);
+
+        if steps == 0 {
+            return offset == [0; 3];
+        }
+
+        // The point has to be a whole number of steps away from the start.
+        let axis = step.iter().position(|&s| s != 0).unwrap();
+        let k = offset[axis] / step[axis];
+
+        (0..=steps).contains(&k) && (0..3).all(|axis| k * step[axis] == offset[axis])
+    }
+
+    pub fn covers_with(&self, point: Point, rasterisation: Rasterisation) -> bool {
+        match rasterisation {
+            Rasterisation::Exact => self.covers(point),
+            Rasterisation::Bresenham => self.bounding_box().contains(point) &&
+                self.rasterise(rasterisation).any(|p| p == point),
         }
     }
 
     fn is_diagonal(&self) -> bool {
         self.start.is_at_45_deg(self.end)
     }
+
+    pub fn direction(&self) -> Direction {
+        match self.start.delta(self.end) {
+            [0, 0, 0] => Direction::Point,
+            [_, 0, 0] => Direction::Horizontal,
+            [0, _, 0] => Direction::Vertical,
+            [0, 0, _] => Direction::Depth,
+            _ if self.is_diagonal() => Direction::Diagonal,
+            _ => Direction::Other,
+        }
+    }
+
+    /// The smallest step between two integer points on this line, and the
+    /// number of such steps from start to end.
+    fn step(&self) -> ([i64; 3], i64) {
+        let start = self.start.coordinates();
+        let end = self.end.coordinates();
+        let delta = [0, 1, 2].map(|axis| end[axis] - start[axis]);
+        let steps = delta.iter().fold(0, |steps, d| gcd(steps, d.abs()));
+
+        if steps == 0 {
+            ([0; 3], 0)
+        } else {
+            (delta.map(|d| d / steps), steps)
+        }
+    }
+
+    /// The points exactly on this line, from start to end.
+    pub fn points(&self) -> Points {
+        let (step, steps) = self.step();
+
+        Points {
+            next: self.start,
+            remaining: steps as usize + 1,
+            stepper: Stepper::Exact { step },
+        }
+    }
+
+    /// The points covered by this line according to the given rasterisation,
+    /// from start to end.
+    pub fn rasterise(&self, rasterisation: Rasterisation) -> Points {
+        match rasterisation {
+            Rasterisation::Exact => self.points(),
+            Rasterisation::Bresenham => {
+                let delta = self.start.delta(self.end).map(|d| d as i64);
+                let major = delta.into_iter().max().unwrap();
+                let start = self.start.coordinates();
+                let end = self.end.coordinates();
+
+                Points {
+                    next: self.start,
+                    remaining: major as usize + 1,
+                    stepper: Stepper::Bresenham {
+                        delta,
+                        major,
+                        step: [0, 1, 2].map(|axis| (end[axis] - start[axis]).signum()),
+                        error: delta.map(|d| 2 * d - major),
+                    },
+                }
+            }
+        }
+    }
+
+    /// The number of points exactly on this line.
+    pub fn len(&self) -> usize {
+        self.points().len()
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        BoundingBox::new(self.start, self.end)
+    }
+
+    /// Whether any of the points exactly on this line lies within the region.
+    pub fn touches(&self, region: &BoundingBox) -> bool {
+        self.steps_within(region).is_some()
+    }
+
+    /// The first and last step from the start for which the point on this
+    /// line lies within the region, None if no point does.
+    pub fn steps_within(&self, region: &BoundingBox) -> Option<(i64, i64)> {
+        let (step, steps) = self.step();
+        let (mut first, mut last) = (0, steps);
+
+        let start = self.start.coordinates();
+        let min = region.min.coordinates();
+        let max = region.max.coordinates();
+
+        for axis in 0..3 {
+            // Solve min <= start + k * step <= max for k.
+            let (low, high) = (min[axis] - start[axis], max[axis] - start[axis]);
+            let step = step[axis];
+
+            match step.signum() {
+                0 if low > 0 || high < 0 => return None,
+                0 => {}
+                1 => {
+                    first = first.max(div_ceil(low, step));
+                    last = last.min(div_floor(high, step));
+                }
+                _ => {
+                    first = first.max(div_ceil(high, step));
+                    last = last.min(div_floor(low, step));
+                }
+            }
+        }
+
+        (first <= last).then_some((first, last))
+    }
+
+    /// The point the given number of steps from the start.
+    pub fn point_at_step(&self, k: i64) -> Point {
+        let (step, _) = self.step();
+        let start = self.start.coordinates();
+
+        [0, 1, 2].map(|axis| start[axis] + k * step[axis]).into()
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+fn div_floor(a: i64, b: i64) -> i64 {
+    let quotient = a / b;
+
+    if a % b != 0 && (a < 0) != (b < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
 }
 
+fn div_ceil(a: i64, b: i64) -> i64 {
+    -div_floor(-a, b)
+}
+
+/// Iterator over the points covered by a line.
+pub struct Points {
+    next: Point,
+    remaining: usize,
+    stepper: Stepper,
+}
+
+enum Stepper {
+    Exact { step: [i64; 3] },
+    /// Every axis keeps its own error term, and moves whenever the line is
+    /// closer to the next coordinate along it.
+    Bresenham { delta: [i64; 3], major: i64, step: [i64; 3], error: [i64; 3] },
+}
+
+impl Stepper {
+    fn advance(&mut self, point: Point) -> Point {
+        let mut coordinates = point.coordinates();
+
+        match self {
+            Stepper::Exact { step } => {
+                for axis in 0..3 {
+                    coordinates[axis] += step[axis];
+                }
+            }
+            Stepper::Bresenham { delta, major, step, error } => {
+                for axis in 0..3 {
+                    if error[axis] > 0 {
+                        coordinates[axis] += step[axis];
+                        error[axis] -= 2 * *major;
+                    }
+                    error[axis] += 2 * delta[axis];
+                }
+            }
+        }
+
+        coordinates.into()
+    }
+}
+
+impl Iterator for Points {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let point = self.next;
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            self.next = self.stepper.advance(point);
+        }
+
+        Some(point)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Points {}
+
 impl From<&str> for Line {
     fn from(input: &str) -> Self {
         let points = input.split(" -> ")
@@ -89,6 +354,13 @@ impl From<&str> for Line {
     }
 }
 
+/// The lines of the puzzle example in test.txt, for the tests of every
+/// module.
+#[cfg(test)]
+pub(crate) fn example() -> Vec<Line> {
+    include_str!("../test.txt").lines().map(Line::from).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,12 +372,19 @@ mod tests {
         assert_eq!(2, point.y);
     }
 
+    #[test]
+    fn test_negative_point_construction() {
+        let point = Point::from("-1,-2000000");
+        assert_eq!(-1, point.x);
+        assert_eq!(-2_000_000, point.y);
+    }
+
     #[test]
     fn test_line_construction() {
         let line = Line::from("8,0 -> 0,8");
 
-        assert_eq!(Point { x: 8, y: 0 }, line.start);
-        assert_eq!(Point { x: 0, y: 8 }, line.end);
+        assert_eq!(Point { x: 8, y: 0, z: 0 }, line.start);
+        assert_eq!(Point { x: 0, y: 8, z: 0 }, line.end);
     }
 
     #[test]
@@ -133,4 +412,110 @@ mod tests {
         assert!(l1.covers(p));
         assert!(l2.covers(p));
     }
+
+    #[test]
+    fn test_points_from_start_to_end() {
+        let line = Line::from("6,4 -> 2,0");
+        let points = line.points().collect::<Vec<_>>();
+
+        assert_eq!(Direction::Diagonal, line.direction());
+        assert_eq!(5, line.len());
+        assert_eq!(Point { x: 6, y: 4, z: 0 }, points[0]);
+        assert_eq!(Point { x: 5, y: 3, z: 0 }, points[1]);
+        assert_eq!(Point { x: 2, y: 0, z: 0 }, points[4]);
+        assert!(points.iter().all(|&p| line.covers(p)));
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(Direction::Horizontal, Line::from("9,4 -> 3,4").direction());
+        assert_eq!(Direction::Vertical, Line::from("7,0 -> 7,4").direction());
+        assert_eq!(Direction::Other, Line::from("1,0 -> 3,1").direction());
+        assert_eq!(Direction::Point, Line::from("3,3 -> 3,3").direction());
+    }
+
+    #[test]
+    fn test_arbitrary_slope_covers_lattice_points() {
+        let line = Line::from("1,10 -> 10,4");
+        let points = line.points().collect::<Vec<_>>();
+
+        assert_eq!(vec![Point { x: 1, y: 10, z: 0 }, Point { x: 4, y: 8, z: 0 }, Point { x: 7, y: 6, z: 0 }, Point { x: 10, y: 4, z: 0 }], points);
+        assert!(line.covers("4,8".into()));
+        assert!(!line.covers("2,9".into()));
+        assert!(!line.covers("13,2".into()));
+        assert!(!Line::from("1,0 -> 3,1").covers("2,0".into()));
+        assert!(Line::from("3,3 -> 3,3").covers("3,3".into()));
+    }
+
+    #[test]
+    fn test_bresenham_steps_along_major_axis() {
+        let line = Line::from("0,0 -> 4,2");
+        let points = line.rasterise(Rasterisation::Bresenham).collect::<Vec<_>>();
+
+        assert_eq!(5, points.len());
+        assert_eq!(Point { x: 0, y: 0, z: 0 }, points[0]);
+        assert_eq!(Point { x: 2, y: 1, z: 0 }, points[2]);
+        assert_eq!(Point { x: 4, y: 2, z: 0 }, points[4]);
+        assert!(line.covers_with("1,0".into(), Rasterisation::Bresenham) || line.covers_with("1,1".into(), Rasterisation::Bresenham));
+        assert!(!line.covers("1,0".into()));
+    }
+
+    #[test]
+    fn test_three_dimensional_lines() {
+        let line = Line::from("0,0,0 -> 4,2,6");
+        let points = line.points().collect::<Vec<_>>();
+
+        assert_eq!(vec![Point { x: 0, y: 0, z: 0 }, Point { x: 2, y: 1, z: 3 }, Point { x: 4, y: 2, z: 6 }], points);
+        assert!(line.covers("2,1,3".into()));
+        assert!(!line.covers("2,1".into()));
+        assert_eq!(Direction::Other, line.direction());
+        assert_eq!(Direction::Diagonal, Line::from("1,1,1 -> 3,-1,3").direction());
+        assert_eq!(Direction::Diagonal, Line::from("1,1,1 -> 3,3,1").direction());
+        assert_eq!(Direction::Depth, Line::from("1,1,1 -> 1,1,3").direction());
+        assert_eq!(Point { x: 2, y: 9, z: 0 }, Point::from("2,9"));
+    }
+
+    #[test]
+    fn test_axis_aligned_points_are_not_at_45_deg() {
+        let origin = Point::default();
+
+        assert!(!origin.is_at_45_deg((5, 0, 0).into()));
+        assert!(!origin.is_at_45_deg((0, -3, 0).into()));
+        assert!(!origin.is_at_45_deg(origin));
+        assert!(!origin.is_at_45_deg((2, 2, 1).into()));
+        assert!(origin.is_at_45_deg((2, -2, 0).into()));
+        assert!(origin.is_at_45_deg((0, 4, 4).into()));
+    }
+
+    #[test]
+    fn test_three_dimensional_bresenham() {
+        let line = Line::from("0,0,0 -> 6,3,2");
+        let points = line.rasterise(Rasterisation::Bresenham).collect::<Vec<_>>();
+
+        assert_eq!(7, points.len());
+        assert_eq!(Point { x: 6, y: 3, z: 2 }, points[6]);
+        assert!(points.windows(2).all(|w| w[1].x - w[0].x == 1 && w[0].delta(w[1]).iter().all(|&d| d <= 1)));
+    }
+
+    #[test]
+    fn test_touches_region() {
+        let line = Line::from("10,4 -> 1,10");
+        let region = |from: &str, to: &str| BoundingBox::new(from.into(), to.into());
+
+        assert!(line.touches(&region("3,7", "5,9")));
+        assert!(!line.touches(&region("5,7", "6,9")));
+        assert_eq!(Some((1, 2)), line.steps_within(&region("0,0", "7,8")));
+        assert_eq!(Point { x: 4, y: 8, z: 0 }, line.point_at_step(2));
+        assert!(Line::from("3,0 -> 3,9").touches(&region("3,9", "7,12")));
+        assert!(!Line::from("3,0 -> 3,9").touches(&region("4,0", "7,12")));
+    }
+
+    #[test]
+    fn test_bresenham_matches_exact_for_straight_lines() {
+        for input in ["0,9 -> 5,9", "8,0 -> 0,8", "7,0 -> 7,4", "6,4 -> 2,0"] {
+            let line = Line::from(input);
+
+            assert!(line.points().eq(line.rasterise(Rasterisation::Bresenham)));
+        }
+    }
 }
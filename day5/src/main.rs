@@ -1,43 +1,172 @@
-#![feature(int_abs_diff)]
-
+mod bounding_box;
+mod intersection;
 mod line;
+mod overlap;
+mod render;
+mod vent_field;
 
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Write};
 use std::path::Path;
+use bounding_box::BoundingBox;
+use intersection::Intersection;
 use line::*;
+use overlap::Coverage;
+use render::Format;
+use vent_field::VentField;
 
 fn main() {
-    let lines = read_lines("input.txt");
+    let mut input = "input.txt".to_owned();
+    let mut queries: Vec<Point> = vec![];
+    let mut stats = false;
+    let mut intersections = false;
+    let mut render_format: Option<Format> = None;
+    let mut output: Option<String> = None;
+    let mut query = false;
+    let mut rasterisation = Rasterisation::Exact;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stats" => stats = true,
+            "--intersections" => intersections = true,
+            "--query" => query = true,
+            "--render" => render_format = Some(args.next().expect("--render expects a format.").as_str().into()),
+            "--output" => output = Some(args.next().expect("--output expects a path.")),
+            "--bresenham" => rasterisation = Rasterisation::Bresenham,
+            "--point" => queries.push(args.next().expect("--point expects x,y.").as_str().into()),
+            _ => input = arg,
+        }
+    }
+
+    let lines = read_lines(input);
 
     let lines: Vec<Line> = lines.into_iter()
         .map(|l| l.as_str().into())
         .collect();
 
-    let x_max = lines.iter()
-        .map(|l| if l.start.x > l.end.x { l.start } else { l.end })
-        .map(|p| p.x)
-        .max()
-        .unwrap();
-
-    let y_max = lines.iter()
-        .map(|l| if l.start.y > l.end.y { l.start } else { l.end })
-        .map(|p| p.y)
-        .max()
-        .unwrap();
-
-    let seen_twice = (0..=y_max).into_iter()
-        .flat_map(|row| (0..=x_max).into_iter().map(move |col| (row, col)))
-        .map(|p| p.into())
-        .map(|p| lines.iter()
-            .filter(|l| l.covers(p))
-            .count())
-        .filter(|&count| count >= 2)
-        .count();
+    if query {
+        answer_queries(&lines);
+        return;
+    }
+
+    let coverage = Coverage::new(&lines, rasterisation);
+
+    if stats {
+        print_stats(&lines, &coverage);
+    }
+
+    for point in queries {
+        let covering = lines.iter()
+            .enumerate()
+            .filter(|(_, l)| l.covers_with(point, rasterisation))
+            .map(|(i, _)| (i + 1).to_string())
+            .collect::<Vec<_>>();
+
+        println!(
+            "Point {} is covered by {} lines: {}",
+            point, coverage.count_at(point), covering.join(", ")
+        );
+    }
+
+    if let Some(format) = render_format {
+        let rendered = match render::render(&coverage, format) {
+            Ok(rendered) => rendered,
+            Err(error) => {
+                eprintln!("Can't render the coverage: {}", error);
+                return;
+            }
+        };
+
+        match output {
+            Some(ref path) => fs::write(path, rendered).expect("Failed to write the rendering."),
+            None => io::stdout().write_all(&rendered).expect("Failed to write the rendering."),
+        }
+        return;
+    }
+
+    if intersections {
+        print_intersections(&lines);
+    }
+
+    let seen_twice = coverage.points_covered(2);
 
     println!("Number of points covered twice: {}", seen_twice);
 }
 
+fn print_stats(lines: &[Line], coverage: &Coverage) {
+    for direction in [Direction::Point, Direction::Horizontal, Direction::Vertical, Direction::Depth, Direction::Diagonal, Direction::Other] {
+        let (count, length) = lines.iter()
+            .filter(|l| l.direction() == direction)
+            .fold((0, 0), |(count, length), l| (count + 1, length + l.len()));
+
+        println!("{:?} lines: {} covering {} points", direction, count, length);
+    }
+
+    if let Some(bounding_box) = BoundingBox::of_lines(lines) {
+        println!(
+            "Bounding box: {} -> {}",
+            bounding_box.min, bounding_box.max
+        );
+    }
+
+    println!("Coverage is stored {}", if coverage.is_sparse() { "sparsely" } else { "densely" });
+}
+
+fn print_intersections(lines: &[Line]) {
+    let intersections = match intersection::find_intersections(lines) {
+        Ok(intersections) => intersections,
+        Err(error) => {
+            eprintln!("Can't find the intersections: {}", error);
+            return;
+        }
+    };
+
+    for junction in intersections.junctions.iter() {
+        println!("{} lines meet at {}", junction.multiplicity(), junction.point);
+    }
+
+    for crossing in intersections.crossings.iter() {
+        if let Intersection::Overlap(from, to) = crossing.intersection {
+            println!(
+                "Lines {} and {} overlap from {} to {}",
+                crossing.lines.0 + 1, crossing.lines.1 + 1, from, to
+            );
+        }
+    }
+
+    println!("Number of intersecting pairs of lines: {}", intersections.crossings.len());
+}
+
+/// Answer coverage queries read from stdin, one per line: either a point
+/// `x,y` or a region `x,y -> x,y`.
+fn answer_queries(lines: &[Line]) {
+    let field = VentField::new(lines);
+    let format = |indices: Vec<usize>| indices.iter()
+        .map(|i| (i + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    for query in io::stdin().lock().lines() {
+        let query = query.expect("Failed to read query.");
+        let query = query.trim();
+
+        if query.is_empty() {
+            continue;
+        }
+
+        if query.contains("->") {
+            let corners = Line::from(query);
+            let touching = field.lines_touching(&corners.bounding_box());
+            println!("{} lines touch {}: {}", touching.len(), query, format(touching));
+        } else {
+            let covering = field.lines_covering(query.into());
+            println!("{} lines cover {}: {}", covering.len(), query, format(covering));
+        }
+    }
+}
+
 // The output is wrapped in a Result to allow matching on errors
 // Returns a vector of the lines of the file.
 //
@@ -49,7 +178,6 @@ where P: AsRef<Path>, {
 
     io::BufReader::new(file)
         .lines()
-        .into_iter()
         .map(|l| l.expect("Failed to read line."))
         .collect()
 }
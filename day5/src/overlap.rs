@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use crate::bounding_box::BoundingBox;
+use crate::line::{Line, Point, Rasterisation};
+
+/// A dense array is used while it has at most this many cells per covered
+/// point, otherwise the counts are kept in a map.
+const MAX_CELLS_PER_POINT: u128 = 16;
+
+/// How many lines cover every point of the bounding box of a set of lines.
+/// The counts are found by walking along each line, so building the coverage
+/// takes time proportional to the total length of the lines.
+pub struct Coverage {
+    bounding_box: BoundingBox,
+    counts: Counts,
+}
+
+enum Counts {
+    Dense(Vec<u32>),
+    Sparse(HashMap<Point, u32>),
+}
+
+impl Coverage {
+    pub fn new(lines: &[Line], rasterisation: Rasterisation) -> Self {
+        let bounding_box = BoundingBox::of_lines(lines)
+            .unwrap_or_else(|| BoundingBox::new((0, 0).into(), (0, 0).into()));
+
+        let covered_points = lines.iter()
+            .map(|l| l.rasterise(rasterisation).len() as u128)
+            .sum::<u128>();
+
+        let counts = if bounding_box.volume() <= MAX_CELLS_PER_POINT * covered_points.max(1) {
+            Counts::Dense(vec![0; bounding_box.volume() as usize])
+        } else {
+            Counts::Sparse(HashMap::with_capacity(covered_points as usize))
+        };
+
+        let mut coverage = Coverage { bounding_box, counts };
+
+        for point in lines.iter().flat_map(|l| l.rasterise(rasterisation)) {
+            match coverage.counts {
+                Counts::Dense(ref mut counts) => {
+                    let index = index(&coverage.bounding_box, point).unwrap();
+                    counts[index] += 1;
+                }
+                Counts::Sparse(ref mut counts) => *counts.entry(point).or_insert(0) += 1,
+            }
+        }
+
+        coverage
+    }
+
+    /// The number of lines covering the given point.
+    pub fn count_at(&self, point: Point) -> u32 {
+        match self.counts {
+            Counts::Dense(ref counts) => index(&self.bounding_box, point)
+                .map_or(0, |index| counts[index]),
+            Counts::Sparse(ref counts) => counts.get(&point).copied().unwrap_or(0),
+        }
+    }
+
+    /// The number of points covered by at least `min` lines.
+    pub fn points_covered(&self, min: u32) -> usize {
+        match self.counts {
+            Counts::Dense(ref counts) => counts.iter()
+                .filter(|&&count| count >= min)
+                .count(),
+            Counts::Sparse(ref counts) => counts.values()
+                .filter(|&&count| count >= min)
+                .count(),
+        }
+    }
+
+    /// The highest number of lines covering a single point.
+    pub fn max_count(&self) -> u32 {
+        match self.counts {
+            Counts::Dense(ref counts) => counts.iter().copied().max(),
+            Counts::Sparse(ref counts) => counts.values().copied().max(),
+        }.unwrap_or(0)
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        self.bounding_box
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.counts, Counts::Sparse(_))
+    }
+}
+
+fn index(bounding_box: &BoundingBox, point: Point) -> Option<usize> {
+    if !bounding_box.contains(point) {
+        return None;
+    }
+
+    let x = point.x.abs_diff(bounding_box.min.x);
+    let y = point.y.abs_diff(bounding_box.min.y);
+    let z = point.z.abs_diff(bounding_box.min.z);
+
+    Some(((z * bounding_box.height() + y) * bounding_box.width() + x) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines() -> Vec<Line> {
+        crate::line::example()
+    }
+
+    #[test]
+    fn test_coverage_matches_covers() {
+        let lines = lines();
+        let coverage = Coverage::new(&lines, Rasterisation::Exact);
+
+        for y in 0..12 {
+            for x in 0..12 {
+                let point = Point { x, y, z: 0 };
+                let expected = lines.iter().filter(|l| l.covers(point)).count();
+
+                assert_eq!(expected as u32, coverage.count_at(point));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sparse_coverage_for_distant_lines() {
+        let lines = ["-3000000,5 -> -2999990,5", "-2999995,0 -> -2999995,10", "4000000,-4000000 -> 4000002,-3999998"]
+            .into_iter()
+            .map(Line::from)
+            .collect::<Vec<_>>();
+        let coverage = Coverage::new(&lines, Rasterisation::Exact);
+
+        assert!(coverage.is_sparse());
+        assert_eq!(2, coverage.count_at((-2999995, 5).into()));
+        assert_eq!(1, coverage.count_at((4000001, -3999999).into()));
+        assert_eq!(1, coverage.points_covered(2));
+    }
+
+    #[test]
+    fn test_coverage_in_space() {
+        let lines = ["0,0,0 -> 4,4,4", "4,0,0 -> 0,4,4", "2,2,0 -> 2,2,4", "0,0,2 -> 4,4,2"]
+            .into_iter()
+            .map(Line::from)
+            .collect::<Vec<_>>();
+        let coverage = Coverage::new(&lines, Rasterisation::Exact);
+
+        assert_eq!(4, coverage.count_at((2, 2, 2).into()));
+        assert_eq!(1, coverage.count_at((2, 2).into()));
+        assert_eq!(0, coverage.count_at((2, 3, 2).into()));
+        assert_eq!(1, coverage.points_covered(2));
+    }
+
+    #[test]
+    fn test_points_covered_twice() {
+        assert_eq!(12, Coverage::new(&lines(), Rasterisation::Exact).points_covered(2));
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::{self, Display};
+
+use crate::line::Point;
+use crate::overlap::Coverage;
+
+/// Images with more pixels than this are refused, as a sparse coverage can
+/// span an enormous area.
+const MAX_PIXELS: u128 = 1 << 28;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// The diagram style of the puzzle: `.` for uncovered points, the number
+    /// of lines otherwise and `#` for ten or more.
+    Ascii,
+    /// Greyscale image, brighter points are covered by more lines.
+    Pgm,
+    /// Colour image, going from black through red and yellow to white.
+    Ppm,
+}
+
+impl From<&str> for Format {
+    fn from(input: &str) -> Self {
+        match input {
+            "ascii" => Format::Ascii,
+            "pgm" => Format::Pgm,
+            "ppm" => Format::Ppm,
+            _ => panic!("Unknown render format '{}', expected ascii, pgm or ppm.", input),
+        }
+    }
+}
+
+/// Why the coverage can't be rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderError {
+    /// The lines span this many planes along the z axis.
+    NotPlanar(u64),
+    /// The image would have this many pixels.
+    TooLarge(u128),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::NotPlanar(depth) => write!(
+                f, "the lines span {} planes along z, only lines in a single plane can be rendered", depth
+            ),
+            RenderError::TooLarge(pixels) => write!(
+                f, "refusing to render {} points, the limit is {}", pixels, MAX_PIXELS
+            ),
+        }
+    }
+}
+
+/// Render the coverage counts, cropped to the bounding box of the lines.
+pub fn render(coverage: &Coverage, format: Format) -> Result<Vec<u8>, RenderError> {
+    let bounding_box = coverage.bounding_box();
+    if !bounding_box.is_planar() {
+        return Err(RenderError::NotPlanar(bounding_box.depth()));
+    }
+    if bounding_box.volume() > MAX_PIXELS {
+        return Err(RenderError::TooLarge(bounding_box.volume()));
+    }
+
+    Ok(match format {
+        Format::Ascii => ascii(coverage).into_bytes(),
+        Format::Pgm => image(coverage, "P5", |intensity| vec![intensity]),
+        Format::Ppm => image(coverage, "P6", heat),
+    })
+}
+
+fn rows(coverage: &Coverage) -> impl Iterator<Item = impl Iterator<Item = u32> + '_> + '_ {
+    let bounding_box = coverage.bounding_box();
+
+    (bounding_box.min.y..=bounding_box.max.y)
+        .map(move |y| (bounding_box.min.x..=bounding_box.max.x)
+            .map(move |x| coverage.count_at(Point { x, y, z: bounding_box.min.z })))
+}
+
+pub fn ascii(coverage: &Coverage) -> String {
+    let mut output = String::new();
+
+    for row in rows(coverage) {
+        output.extend(row.map(|count| match count {
+            0 => '.',
+            1..=9 => char::from_digit(count, 10).unwrap(),
+            _ => '#',
+        }));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// A binary netpbm image, where every point is mapped to its pixel bytes by
+/// the given function of its intensity.
+fn image(coverage: &Coverage, magic: &str, pixel: impl Fn(u8) -> Vec<u8>) -> Vec<u8> {
+    let bounding_box = coverage.bounding_box();
+    let max_count = coverage.max_count().max(1);
+
+    let mut output = format!(
+        "{}\n{} {}\n255\n",
+        magic, bounding_box.width(), bounding_box.height()
+    ).into_bytes();
+
+    for row in rows(coverage) {
+        for count in row {
+            let intensity = (count as u64 * 255 / max_count as u64) as u8;
+            output.extend(pixel(intensity));
+        }
+    }
+
+    output
+}
+
+/// A black body colour map: black, red, yellow, white.
+fn heat(intensity: u8) -> Vec<u8> {
+    let scaled = intensity as u32 * 3;
+
+    vec![
+        scaled.min(255) as u8,
+        scaled.saturating_sub(255).min(255) as u8,
+        scaled.saturating_sub(510).min(255) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line::{example, Line, Rasterisation};
+
+    fn coverage() -> Coverage {
+        Coverage::new(&example(), Rasterisation::Exact)
+    }
+
+    #[test]
+    fn test_ascii_matches_puzzle_diagram() {
+        let expected = "\
+1.1....11.
+.111...2..
+..2.1.111.
+...1.2.2..
+.112313211
+...1.2....
+..1...1...
+.1.....1..
+1.......1.
+222111....
+";
+
+        assert_eq!(expected, ascii(&coverage()));
+    }
+
+    #[test]
+    fn test_pgm_header_and_size() {
+        let pgm = render(&coverage(), Format::Pgm).unwrap();
+        let header = b"P5\n10 10\n255\n";
+
+        assert_eq!(header, &pgm[..header.len()]);
+        assert_eq!(header.len() + 100, pgm.len());
+        assert_eq!(255, *pgm.iter().skip(header.len()).max().unwrap());
+    }
+
+    #[test]
+    fn test_ppm_has_three_channels() {
+        let ppm = render(&coverage(), Format::Ppm).unwrap();
+
+        assert_eq!(b"P6\n10 10\n255\n".len() + 300, ppm.len());
+        assert_eq!(vec![255, 255, 255], heat(255));
+        assert_eq!(vec![0, 0, 0], heat(0));
+    }
+
+    #[test]
+    fn test_lines_in_space_are_reported() {
+        let lines = [Line::from("8,0,-2 -> 0,8,2"), Line::from("9,4 -> 3,4")];
+        let coverage = Coverage::new(&lines, Rasterisation::Exact);
+        assert_eq!(Err(RenderError::NotPlanar(5)), render(&coverage, Format::Ascii));
+
+        // A single plane renders wherever it lies.
+        let coverage = Coverage::new(&[Line::from("1,1,3 -> 3,1,3")], Rasterisation::Exact);
+        assert_eq!(Ok(b"111\n".to_vec()), render(&coverage, Format::Ascii));
+    }
+}
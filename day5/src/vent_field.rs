@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use crate::bounding_box::BoundingBox;
+use crate::line::{Line, Point};
+
+/// The lines bucketed by the square cells of a coarse grid they pass through,
+/// so coverage queries only have to test the lines near the queried points.
+/// The grid is in the xy-plane, lines in space are bucketed by the cells they
+/// pass over.
+pub struct VentField<'a> {
+    lines: &'a [Line],
+    bucket_size: i64,
+    buckets: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl<'a> VentField<'a> {
+    /// Index the lines with buckets sized such that there are about as many
+    /// buckets as lines in their bounding box.
+    pub fn new(lines: &'a [Line]) -> Self {
+        let bucket_size = BoundingBox::of_lines(lines)
+            .map_or(1, |b| ((b.width() as u128 * b.height() as u128 / lines.len() as u128) as f64).sqrt() as i64)
+            .max(1);
+
+        Self::with_bucket_size(lines, bucket_size)
+    }
+
+    pub fn with_bucket_size(lines: &'a [Line], bucket_size: i64) -> Self {
+        assert!(bucket_size > 0, "The bucket size has to be positive.");
+
+        let mut field = VentField {
+            lines,
+            bucket_size,
+            buckets: HashMap::new(),
+        };
+
+        for (index, line) in lines.iter().enumerate() {
+            let bounding_box = line.bounding_box();
+            let (min_column, _) = field.bucket_of(bounding_box.min);
+            let (max_column, _) = field.bucket_of(bounding_box.max);
+
+            // Within a column of buckets the line is a single run of points,
+            // so it passes through all buckets between its ends there.
+            for column in min_column..=max_column {
+                let strip = BoundingBox::new(
+                    Point { x: column * bucket_size, ..bounding_box.min },
+                    Point { x: (column + 1) * bucket_size - 1, ..bounding_box.max },
+                );
+
+                if let Some((first, last)) = line.steps_within(&strip) {
+                    let (_, row_a) = field.bucket_of(line.point_at_step(first));
+                    let (_, row_b) = field.bucket_of(line.point_at_step(last));
+
+                    for row in i64::min(row_a, row_b)..=i64::max(row_a, row_b) {
+                        field.buckets.entry((column, row)).or_default().push(index);
+                    }
+                }
+            }
+        }
+
+        field
+    }
+
+    fn bucket_of(&self, point: Point) -> (i64, i64) {
+        (point.x.div_euclid(self.bucket_size), point.y.div_euclid(self.bucket_size))
+    }
+
+    /// The indices of the lines covering the point, in ascending order.
+    pub fn lines_covering(&self, point: Point) -> Vec<usize> {
+        self.buckets.get(&self.bucket_of(point))
+            .map(|candidates| candidates.iter()
+                .copied()
+                .filter(|&i| self.lines[i].covers(point))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// The indices of the lines covering at least one point of the region, in
+    /// ascending order.
+    pub fn lines_touching(&self, region: &BoundingBox) -> Vec<usize> {
+        let (min_column, min_row) = self.bucket_of(region.min);
+        let (max_column, max_row) = self.bucket_of(region.max);
+        let region_buckets = (max_column - min_column + 1) as u128 * (max_row - min_row + 1) as u128;
+
+        let mut candidates = if region_buckets <= self.buckets.len() as u128 {
+            (min_column..=max_column)
+                .flat_map(|column| (min_row..=max_row).map(move |row| (column, row)))
+                .filter_map(|bucket| self.buckets.get(&bucket))
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>()
+        } else {
+            self.buckets.iter()
+                .filter(|(&(column, row), _)| {
+                    (min_column..=max_column).contains(&column) && (min_row..=max_row).contains(&row)
+                })
+                .flat_map(|(_, lines)| lines.iter().copied())
+                .collect::<Vec<_>>()
+        };
+
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates.retain(|&i| self.lines[i].touches(region));
+
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The puzzle example with a line reaching into negative coordinates.
+    fn lines() -> Vec<Line> {
+        let mut lines = crate::line::example();
+        lines.push(Line::from("-7,-3 -> 11,9"));
+
+        lines
+    }
+
+    #[test]
+    fn test_point_queries_match_covers() {
+        let lines = lines();
+
+        for bucket_size in [1, 3, 4, 100] {
+            let field = VentField::with_bucket_size(&lines, bucket_size);
+
+            for y in -5..12 {
+                for x in -8..12 {
+                    let point = Point { x, y, z: 0 };
+                    let expected = (0..lines.len())
+                        .filter(|&i| lines[i].covers(point))
+                        .collect::<Vec<_>>();
+
+                    assert_eq!(expected, field.lines_covering(point));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_region_queries_match_points() {
+        let lines = lines();
+        let field = VentField::new(&lines);
+
+        for (min, max) in [((0, 0), (2, 2)), ((5, 5), (5, 5)), ((-10, -10), (20, 20)), ((9, 0), (9, 3))] {
+            let region = BoundingBox::new(min.into(), max.into());
+            let expected = (0..lines.len())
+                .filter(|&i| lines[i].points().any(|p| region.contains(p)))
+                .collect::<Vec<_>>();
+
+            assert_eq!(expected, field.lines_touching(&region));
+        }
+    }
+}
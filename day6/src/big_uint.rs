@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul};
+
+/// An unsigned integer of arbitrary size, stored as little-endian 64-bit
+/// limbs without trailing zero limbs, so zero has no limbs at all.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint { limbs: vec![] }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// The value as a u64, None if it doesn't fit.
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.len() {
+            0 => Some(0),
+            1 => Some(self.limbs[0]),
+            _ => None,
+        }
+    }
+
+    /// The natural logarithm, negative infinity for zero. Only the two most
+    /// significant limbs are taken into account, which is plenty for an f64.
+    pub fn ln(&self) -> f64 {
+        let len = self.limbs.len();
+        let top = match len {
+            0 => return f64::NEG_INFINITY,
+            1 => self.limbs[0] as f64,
+            _ => self.limbs[len - 1] as f64 * 2f64.powi(64) + self.limbs[len - 2] as f64,
+        };
+
+        top.ln() + (len.saturating_sub(2) * 64) as f64 * std::f64::consts::LN_2
+    }
+
+    /// The quotient and remainder of dividing by a single limb.
+    pub fn div_rem(&self, divisor: u64) -> (BigUint, u64) {
+        assert!(divisor != 0, "Division by zero.");
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut remainder = 0u128;
+
+        for (i, &limb) in self.limbs.iter().enumerate().rev() {
+            let current = remainder << 64 | limb as u128;
+            quotient[i] = (current / divisor as u128) as u64;
+            remainder = current % divisor as u128;
+        }
+
+        (BigUint::from_limbs(quotient), remainder as u64)
+    }
+
+    fn from_limbs(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+
+        BigUint { limbs }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        BigUint::from_limbs(vec![value])
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, other: &BigUint) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+
+        let mut carry = false;
+
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let (sum, overflow_a) = limb.overflowing_add(other.limbs.get(i).copied().unwrap_or(0));
+            let (sum, overflow_b) = sum.overflowing_add(carry as u64);
+
+            *limb = sum;
+            carry = overflow_a || overflow_b;
+
+            if !carry && i >= other.limbs.len() {
+                break;
+            }
+        }
+
+        if carry {
+            self.limbs.push(1);
+        }
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut sum = self.clone();
+        sum += other;
+
+        sum
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    /// Schoolbook multiplication, quadratic in the number of limbs.
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+
+        let mut limbs = vec![0u64; self.limbs.len() + other.limbs.len()];
+
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u128;
+
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let current = limbs[i + j] as u128 + a as u128 * b as u128 + carry;
+                limbs[i + j] = current as u64;
+                carry = current >> 64;
+            }
+
+            limbs[i + other.limbs.len()] = carry as u64;
+        }
+
+        BigUint::from_limbs(limbs)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs.len().cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    /// Decimal, found by splitting off 19 digits at a time.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+        let mut chunks = vec![];
+        let mut rest = self.clone();
+
+        loop {
+            let (quotient, remainder) = rest.div_rem(CHUNK);
+            chunks.push(remainder);
+            rest = quotient;
+
+            if rest.is_zero() {
+                break;
+            }
+        }
+
+        let mut digits = chunks.pop().unwrap().to_string();
+        chunks.iter().rev().for_each(|chunk| digits.push_str(&format!("{:019}", chunk)));
+
+        f.pad_integral(true, "", &digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::Rng;
+
+    #[test]
+    fn test_matches_u128_arithmetic() {
+        // Shift the numbers by a random amount to cover every size of limb.
+        let mut rng = Rng::new(2021);
+        let mut random = || {
+            let value = rng.next_u64();
+            value >> (value % 64)
+        };
+
+        for _ in 0..1000 {
+            let (a, b, c) = (random(), random(), random());
+            let product = &BigUint::from(a) * &BigUint::from(b);
+            let sum = &product + &BigUint::from(c);
+            let expected = a as u128 * b as u128 + c as u128;
+
+            assert_eq!(expected.to_string(), sum.to_string());
+            assert_eq!((expected % 1_000_007) as u64, sum.div_rem(1_000_007).1);
+            assert_eq!(u64::try_from(expected).ok(), sum.to_u64());
+        }
+    }
+
+    #[test]
+    fn test_powers_of_two() {
+        let two = BigUint::from(2);
+        let mut power = BigUint::from(1);
+
+        for _ in 0..200 {
+            power = &power * &two;
+        }
+
+        assert_eq!("1606938044258990275541962092341162602522202993782792835301376", power.to_string());
+        assert!(power > &power.div_rem(2).0 + &BigUint::from(u64::MAX));
+        assert_eq!("0", BigUint::from(0).to_string());
+        assert!((power.ln() - 200.0 * std::f64::consts::LN_2).abs() < 1e-9);
+        assert_eq!("  42", format!("{:>4}", BigUint::from(42)));
+    }
+}
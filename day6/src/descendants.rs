@@ -0,0 +1,85 @@
+use crate::big_uint::BigUint;
+use crate::population::{School, Species};
+
+/// For every timer value and every day up to a limit, the number of fish a
+/// single fish with that timer has turned into by then, itself included.
+/// The count for a whole school is then a weighted sum over its age bins.
+pub struct DescendantTable {
+    species: Species,
+    days: Vec<Vec<BigUint>>,
+}
+
+impl DescendantTable {
+    /// Build the table for days 0 to `max_day`, in O(max_day * ages).
+    pub fn new(species: Species, max_day: usize) -> Self {
+        let ages = species.ages();
+        let mut days = Vec::with_capacity(max_day + 1);
+        days.push(vec![BigUint::from(1); ages]);
+
+        // After a day a fish at 0 has become one fish at the reset timer and
+        // one newborn, any other fish has just aged by a day.
+        for day in 1..=max_day {
+            let previous: &Vec<BigUint> = &days[day - 1];
+            let counts = (0..ages)
+                .map(|timer| match timer {
+                    0 => &previous[species.reset] + &previous[species.newborn],
+                    _ => previous[timer - 1].clone(),
+                })
+                .collect();
+
+            days.push(counts);
+        }
+
+        DescendantTable { species, days }
+    }
+
+    pub fn max_day(&self) -> usize {
+        self.days.len() - 1
+    }
+
+    /// The number of fish a school has turned into after the given number of
+    /// days.
+    pub fn count(&self, school: &School, day: usize) -> BigUint {
+        assert_eq!(self.species, school.species, "The table is for a different species.");
+        assert!(day <= self.max_day(), "The table only goes up to day {}.", self.max_day());
+
+        school.bins.iter()
+            .zip(self.days[day].iter())
+            .fold(BigUint::zero(), |mut sum, (fish, descendants)| {
+                sum += &(fish * descendants);
+                sum
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::Population;
+
+    #[test]
+    fn test_counts_match_simulation() {
+        let table = DescendantTable::new(Species::default(), 256);
+        let school = School::new(Species::default(), &[3, 4, 3, 1, 2]);
+        let mut population = Population::new(vec![school.clone()]);
+
+        for day in 0..=256 {
+            assert_eq!(population.total(), table.count(&school, day));
+            population.step();
+        }
+
+        assert_eq!(Some(26984457539), table.count(&school, 256).to_u64());
+    }
+
+    #[test]
+    fn test_other_species() {
+        let species = Species::new(3, 1);
+        let table = DescendantTable::new(species, 40);
+        let school = School::new(species, &[0, 2, 2, 3]);
+        let mut population = Population::new(vec![school.clone()]);
+        population.simulate(40);
+
+        assert_eq!(population.total(), table.count(&school, 40));
+        assert_eq!(40, table.max_day());
+    }
+}
@@ -0,0 +1,192 @@
+use crate::big_uint::BigUint;
+use crate::population::Population;
+
+/// Characters for eight levels of a sparkline, lowest first.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The age bins of every school on every day of a simulation, starting with
+/// the initial population on day 0.
+pub struct History {
+    days: Vec<Vec<Vec<BigUint>>>,
+}
+
+impl History {
+    /// Simulate the population day by day, recording every day.
+    pub fn record(population: &mut Population, days: usize) -> Self {
+        let mut history = History { days: Vec::with_capacity(days + 1) };
+        history.push(population);
+
+        for _ in 0..days {
+            population.step();
+            history.push(population);
+        }
+
+        history
+    }
+
+    fn push(&mut self, population: &Population) {
+        self.days.push(population.schools().iter().map(|school| school.bins.clone()).collect());
+    }
+
+    /// The total population on every day.
+    pub fn totals(&self) -> Vec<BigUint> {
+        self.days.iter()
+            .map(|schools| schools.iter().flatten().fold(BigUint::zero(), |mut sum, count| {
+                sum += count;
+                sum
+            }))
+            .collect()
+    }
+
+    /// The factor by which the population grew on every day after day 0.
+    pub fn growth_rates(&self) -> Vec<f64> {
+        self.totals()
+            .windows(2)
+            .map(|pair| (pair[1].ln() - pair[0].ln()).exp())
+            .collect()
+    }
+
+    /// A row per day with the total and the count of every age bin. The bins
+    /// are prefixed with their species if there is more than one.
+    pub fn to_csv(&self) -> String {
+        let schools = self.days.first().map_or(&[][..], |day| &day[..]);
+        let mut header = vec!["day".to_owned(), "total".to_owned()];
+
+        for (species, bins) in schools.iter().enumerate() {
+            header.extend((0..bins.len()).map(|age| match schools.len() {
+                1 => format!("age{}", age),
+                _ => format!("species{}_age{}", species + 1, age),
+            }));
+        }
+
+        let mut csv = header.join(",") + "\n";
+
+        for (day, (schools, total)) in self.days.iter().zip(self.totals()).enumerate() {
+            let row = [day.to_string(), total.to_string()].into_iter()
+                .chain(schools.iter().flatten().map(BigUint::to_string))
+                .collect::<Vec<_>>();
+
+            csv += &row.join(",");
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// The natural logarithm of the totals, sampled down to at most `width`
+    /// evenly spaced days.
+    fn sampled_logs(&self, width: usize) -> Vec<f64> {
+        let logs = self.totals().iter().map(BigUint::ln).collect::<Vec<_>>();
+
+        if logs.len() <= width || width < 2 {
+            return logs;
+        }
+
+        (0..width)
+            .map(|i| logs[i * (logs.len() - 1) / (width - 1)])
+            .collect()
+    }
+
+    /// The totals as a single line of block characters on a log scale.
+    pub fn sparkline(&self, width: usize) -> String {
+        let logs = self.sampled_logs(width);
+        let (min, max) = bounds(&logs);
+
+        logs.iter()
+            .map(|&log| SPARKS[level(log, min, max, SPARKS.len())])
+            .collect()
+    }
+
+    /// A plot of the totals on a log scale, with the lowest and highest total
+    /// marked on the vertical axis.
+    pub fn plot(&self, width: usize, height: usize) -> String {
+        let logs = self.sampled_logs(width);
+        let (min, max) = bounds(&logs);
+        let levels = logs.iter().map(|&log| level(log, min, max, height)).collect::<Vec<_>>();
+        let mut output = String::new();
+
+        for row in (0..height).rev() {
+            let label = match row {
+                _ if row == height - 1 => format!("{:>9.2e}", max.exp()),
+                0 => format!("{:>9.2e}", min.exp()),
+                _ => " ".repeat(9),
+            };
+
+            output += &label;
+            output += " |";
+            output.extend(levels.iter().map(|&level| if level == row { '*' } else { ' ' }));
+            output.push('\n');
+        }
+
+        output += &format!("{:>9} +{}\n", "", "-".repeat(levels.len()));
+        output += &format!("{:>9}  day 0{:>width$}\n", "", self.days.len() - 1, width = levels.len().saturating_sub(5));
+
+        output
+    }
+}
+
+/// The smallest and largest finite value, ignoring days without any fish.
+fn bounds(logs: &[f64]) -> (f64, f64) {
+    logs.iter()
+        .filter(|log| log.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &log| (min.min(log), max.max(log)))
+}
+
+/// Which of the levels the value falls in, linearly between min and max.
+fn level(value: f64, min: f64, max: f64, levels: usize) -> usize {
+    if !value.is_finite() || max <= min {
+        return 0;
+    }
+
+    (((value - min) / (max - min) * (levels - 1) as f64).round() as usize).min(levels - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::{School, Species};
+
+    fn history(days: usize) -> History {
+        let mut population = Population::new(vec![School::new(Species::default(), &[3, 4, 3, 1, 2])]);
+
+        History::record(&mut population, days)
+    }
+
+    #[test]
+    fn test_csv_rows_per_day() {
+        let csv = history(18).to_csv();
+        let rows = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!("day,total,age0,age1,age2,age3,age4,age5,age6,age7,age8", rows[0]);
+        assert_eq!("0,5,0,1,1,2,1,0,0,0,0", rows[1]);
+        assert_eq!("1,5,1,1,2,1,0,0,0,0,0", rows[2]);
+        assert!(rows[19].starts_with("18,26,"));
+        assert_eq!(20, rows.len());
+    }
+
+    #[test]
+    fn test_growth_rates_converge() {
+        let rates = history(3000).growth_rates();
+        let eigenvalue = Species::default().growth_rate();
+
+        // The other eigenvalues are close in magnitude, so it takes a while.
+        assert_eq!(3000, rates.len());
+        assert!((rates[99] - eigenvalue).abs() > 1e-3);
+        assert!((rates[2999] - eigenvalue).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sparkline_and_plot_shapes() {
+        let history = history(256);
+        let sparkline = history.sparkline(40);
+
+        assert_eq!(40, sparkline.chars().count());
+        assert!(sparkline.starts_with('▁') && sparkline.ends_with('█'));
+
+        let plot = history.plot(60, 10);
+        assert_eq!(12, plot.lines().count());
+        assert_eq!(60, plot.matches('*').count());
+        assert!(plot.lines().next().unwrap().ends_with('*'));
+        assert!(plot.lines().nth(9).unwrap().contains("|*"));
+    }
+}
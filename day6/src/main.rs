@@ -1,31 +1,213 @@
-use std::fs::File;
+mod big_uint;
+mod descendants;
+mod history;
+mod matrix;
+mod population;
+mod random;
+mod stochastic;
+
+use std::env;
+use std::fs::{self, File};
 use std::io::{self, BufRead};
 use std::path::Path;
+use std::str::FromStr;
+use descendants::DescendantTable;
+use history::History;
+use population::*;
+use stochastic::{Distribution, StochasticModel};
+
+/// Stepping day by day is fine up to this many days, beyond that the matrix
+/// powers are faster.
+const MAX_STEPPED_DAYS: u64 = 1000;
 
 fn main() {
-    const DAYS: usize = 256;
-    const CYCLE: usize = 8;
-    let line = &read_lines("input.txt")[0];
+    let mut input = "input.txt".to_owned();
+    let mut days: u64 = 256;
+    let mut modulus: Option<u64> = None;
+    let mut use_matrix = false;
+    let mut checked = false;
+    let mut csv: Option<String> = None;
+    let mut plot = false;
+    let mut growth = false;
+    let mut queries: Option<String> = None;
+    let mut intervals: Option<Distribution> = None;
+    let mut mortality: Option<f64> = None;
+    let mut runs: Option<usize> = None;
+    let mut seed: u64 = 0;
+    let mut default_species = Species::default();
+    let mut species: Vec<Species> = vec![];
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--days" => days = parse_number(args.next(), "--days"),
+            "--matrix" => use_matrix = true,
+            "--checked" => checked = true,
+            "--csv" => csv = Some(args.next().expect("--csv expects a path.")),
+            "--plot" => plot = true,
+            "--growth" => growth = true,
+            "--queries" => queries = Some(args.next().expect("--queries expects a path.")),
+            "--interval" => intervals = Some(args.next().expect("--interval expects days or days:weight,...").as_str().into()),
+            "--mortality" => mortality = Some(parse_number(args.next(), "--mortality")),
+            "--monte-carlo" => runs = Some(parse_number(args.next(), "--monte-carlo")),
+            "--seed" => seed = parse_number(args.next(), "--seed"),
+            "--modulus" => modulus = Some(parse_number(args.next(), "--modulus")),
+            "--reset" => default_species.reset = parse_number(args.next(), "--reset"),
+            "--newborn" => default_species.newborn = parse_number(args.next(), "--newborn"),
+            "--species" => species.push(args.next().expect("--species expects reset,newborn.").as_str().into()),
+            _ => input = arg,
+        }
+    }
+
+    if species.is_empty() {
+        species.push(default_species);
+    }
+
+    assert!(
+        species.len() == 1 || queries.is_none(),
+        "Queries only support a single species."
+    );
+
+    let stochastic = intervals.is_some() || mortality.is_some() || runs.is_some();
+    assert!(
+        species.len() == 1 || !stochastic,
+        "The stochastic model only supports a single species."
+    );
+
+    if let Some(path) = queries {
+        answer_queries(path, species[0]);
+        return;
+    }
+
+    // Every line of the input holds the initial timers of one species.
+    let lines = read_lines(input);
+    assert!(
+        lines.len() >= species.len(),
+        "Expected a line of timers for each of the {} species.", species.len()
+    );
 
-    let fish = line.split(',')
-        .map(|num| num.parse::<u64>().expect("Failed to parse number."))
+    let timers = lines.iter()
+        .take(species.len())
+        .map(|line| line.split(',')
+            .map(|num| num.trim().parse::<usize>().expect("Failed to parse number."))
+            .collect::<Vec<_>>())
         .collect::<Vec<_>>();
 
-    let mut bins = vec![0u64; CYCLE + 1];
-    fish.iter().for_each(|&f| bins[f as usize] += 1);
-    
-    for _ in 0..DAYS {
-        let created_fish = bins[0];
+    if stochastic {
+        let model = StochasticModel::new(
+            species[0].newborn,
+            intervals.unwrap_or_else(|| Distribution::fixed(species[0].reset + 1)),
+            mortality.unwrap_or(0.0),
+        );
 
-        for i in 0..CYCLE {
-            bins[i] = bins[i + 1];
+        simulate_stochastic(&model, &timers[0], days as usize, runs, seed);
+        return;
+    }
+
+    let schools = species.into_iter()
+        .zip(timers.iter())
+        .map(|(species, fish)| School::new(species, fish))
+        .collect::<Vec<_>>();
+
+    let mut population = Population::new(schools);
+    assert!(modulus != Some(0), "The modulus has to be positive.");
+    assert!(!checked || modulus.is_none(), "--checked counts exactly, it can't be combined with --modulus.");
+
+    // The history needs the total of every day, so always steps day by day.
+    if csv.is_some() || plot || growth {
+        let history = History::record(&mut population, days as usize);
+
+        if let Some(path) = csv {
+            fs::write(path, history.to_csv()).expect("Failed to write the history.");
         }
 
-        bins[6] += created_fish;
-        bins[CYCLE] = created_fish;
+        if plot {
+            println!("{}", history.sparkline(72));
+            print!("{}", history.plot(72, 16));
+        }
+
+        if growth {
+            print_growth(&history, population.growth_rate());
+        }
+    } else if checked {
+        match population.simulate_checked(days as usize) {
+            Some(day) => println!("The number of fish overflows u64 on day {}", day),
+            None => println!("The number of fish fits in u64 for all {} days", days),
+        }
+    } else if use_matrix || modulus.is_some() || days > MAX_STEPPED_DAYS {
+        // Many days, or counts only needed modulo something, are computed by
+        // matrix powers.
+        population.jump(days, modulus);
+    } else {
+        population.simulate(days as usize);
     }
 
-    println!("Number of fish after {} days: {}", DAYS, bins.iter().sum::<u64>());
+    if population.schools().len() > 1 {
+        for school in population.schools() {
+            println!(
+                "Species resetting to {} with newborns at {}: {}",
+                school.species.reset, school.species.newborn,
+                modulus.map_or_else(|| school.total().to_string(), |m| school.total_modulo(m).to_string())
+            );
+        }
+    }
+
+    match modulus {
+        Some(m) => println!("Number of fish after {} days modulo {}: {}", days, m, population.total_modulo(m)),
+        None => println!("Number of fish after {} days: {}", days, population.total()),
+    }
+}
+
+/// Answer queries of the form `days: timers`, one per line, for a single
+/// species from a table built once up to the largest number of days.
+fn answer_queries<P>(path: P, species: Species)
+where P: AsRef<Path>, {
+    let queries = read_lines(path).into_iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (days, timers) = line.split_once(':').expect("Queries are given as days: timers.");
+            let timers = timers.split(',')
+                .map(|num| num.trim().parse::<usize>().expect("Failed to parse number."))
+                .collect::<Vec<_>>();
+
+            (days.trim().parse::<usize>().expect("Failed to parse days."), School::new(species, &timers))
+        })
+        .collect::<Vec<_>>();
+
+    let max_day = queries.iter().map(|(days, _)| *days).max().unwrap_or(0);
+    let table = DescendantTable::new(species, max_day);
+
+    for (days, school) in queries {
+        println!("{} fish become {} after {} days", school.total(), table.count(&school, days), days);
+    }
+}
+
+fn simulate_stochastic(model: &StochasticModel, timers: &[usize], days: usize, runs: Option<usize>, seed: u64) {
+    let expected = model.expected(timers, days);
+    println!("Expected number of fish after {} days: {:.3}", days, expected[days]);
+
+    if let Some(runs) = runs {
+        let estimate = model.monte_carlo(timers, days, runs, seed);
+
+        println!(
+            "Monte Carlo over {} runs: mean {:.3}, standard deviation {:.3}, 95% confidence interval [{:.3}, {:.3}]",
+            runs, estimate.mean, estimate.std_dev, estimate.low, estimate.high
+        );
+    }
+}
+
+fn print_growth(history: &History, eigenvalue: f64) {
+    for (day, rate) in history.growth_rates().iter().enumerate() {
+        println!("Day {:>4}: growth {:.9}, off by {:+.3e}", day + 1, rate, rate - eigenvalue);
+    }
+
+    println!("Dominant eigenvalue: {:.9}", eigenvalue);
+}
+
+fn parse_number<T: FromStr>(arg: Option<String>, flag: &str) -> T {
+    arg.unwrap_or_else(|| panic!("{} expects a number.", flag))
+        .parse()
+        .unwrap_or_else(|_| panic!("{} expects a number.", flag))
 }
 
 // The output is wrapped in a Result to allow matching on errors
@@ -39,7 +221,6 @@ where P: AsRef<Path>, {
 
     io::BufReader::new(file)
         .lines()
-        .into_iter()
         .map(|l| l.expect("Failed to read line."))
         .collect()
 }
@@ -0,0 +1,136 @@
+use crate::big_uint::BigUint;
+
+/// A square matrix of counts, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Matrix {
+    size: usize,
+    cells: Vec<BigUint>,
+}
+
+impl Matrix {
+    pub fn zero(size: usize) -> Self {
+        Matrix { size, cells: vec![BigUint::zero(); size * size] }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Matrix::zero(size);
+        (0..size).for_each(|i| matrix[(i, i)] = BigUint::from(1));
+
+        matrix
+    }
+
+    /// The matrix product, reduced modulo `modulus` if given.
+    pub fn mul(&self, other: &Matrix, modulus: Option<u64>) -> Matrix {
+        assert_eq!(self.size, other.size, "Matrices of different sizes can't be multiplied.");
+        let mut product = Matrix::zero(self.size);
+
+        for row in 0..self.size {
+            for column in 0..self.size {
+                let products = (0..self.size).map(|k| &self[(row, k)] * &other[(k, column)]);
+
+                product[(row, column)] = reduce(products, modulus);
+            }
+        }
+
+        product
+    }
+
+    /// The product of this matrix with a column vector.
+    pub fn apply(&self, vector: &[BigUint], modulus: Option<u64>) -> Vec<BigUint> {
+        assert_eq!(self.size, vector.len(), "The vector doesn't match the matrix size.");
+
+        (0..self.size)
+            .map(|row| reduce((0..self.size).map(|k| &self[(row, k)] * &vector[k]), modulus))
+            .collect()
+    }
+
+    /// This matrix raised to the given power by repeated squaring, which
+    /// takes O(log exponent) multiplications.
+    pub fn pow(&self, mut exponent: u64, modulus: Option<u64>) -> Matrix {
+        let mut result = Matrix::identity(self.size);
+        let mut base = self.clone();
+
+        if let Some(modulus) = modulus {
+            result.cells.iter_mut().for_each(|cell| *cell = BigUint::from(cell.div_rem(modulus).1));
+        }
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base, modulus);
+            }
+
+            exponent >>= 1;
+
+            if exponent > 0 {
+                base = base.mul(&base, modulus);
+            }
+        }
+
+        result
+    }
+}
+
+/// Sum the products into a single entry.
+fn reduce(products: impl Iterator<Item = BigUint>, modulus: Option<u64>) -> BigUint {
+    let sum = products.fold(BigUint::zero(), |mut sum, product| {
+        sum += &product;
+        sum
+    });
+
+    match modulus {
+        Some(modulus) => BigUint::from(sum.div_rem(modulus).1),
+        None => sum,
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for Matrix {
+    type Output = BigUint;
+
+    fn index(&self, (row, column): (usize, usize)) -> &BigUint {
+        &self.cells[row * self.size + column]
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, column): (usize, usize)) -> &mut BigUint {
+        &mut self.cells[row * self.size + column]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fibonacci() -> Matrix {
+        let mut matrix = Matrix::zero(2);
+        matrix[(0, 0)] = BigUint::from(1);
+        matrix[(0, 1)] = BigUint::from(1);
+        matrix[(1, 0)] = BigUint::from(1);
+
+        matrix
+    }
+
+    #[test]
+    fn test_pow_matches_repeated_multiplication() {
+        let matrix = fibonacci();
+        let mut expected = Matrix::identity(2);
+
+        for exponent in 0..40 {
+            assert_eq!(expected, matrix.pow(exponent, None));
+            expected = expected.mul(&matrix, None);
+        }
+
+        assert_eq!(BigUint::from(102334155), matrix.pow(40, None)[(0, 1)]);
+        assert_eq!("354224848179261915075", matrix.pow(100, None)[(0, 1)].to_string());
+    }
+
+    #[test]
+    fn test_pow_with_modulus() {
+        let matrix = fibonacci();
+
+        assert_eq!(BigUint::from(102334155 % 1000), matrix.pow(40, Some(1000))[(0, 1)]);
+        // Fibonacci numbers modulo 10 repeat every 60 steps.
+        assert_eq!(matrix.pow(7, Some(10)), matrix.pow(7 + 60 * 1_000_000_000_000_000, Some(10)));
+        assert_eq!(vec![BigUint::zero()], Matrix::identity(1).apply(&[BigUint::from(5)], Some(5)));
+    }
+}
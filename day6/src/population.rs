@@ -0,0 +1,308 @@
+use crate::big_uint::BigUint;
+use crate::matrix::Matrix;
+
+/// The reproduction cycle of a species of lanternfish. A fish whose timer is
+/// at 0 restarts at `reset` and spawns a newborn with its timer at `newborn`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Species {
+    pub reset: usize,
+    pub newborn: usize,
+}
+
+impl Species {
+    pub fn new(reset: usize, newborn: usize) -> Self {
+        Species { reset, newborn }
+    }
+
+    /// The number of distinct timer values a fish of this species can have.
+    pub fn ages(&self) -> usize {
+        usize::max(self.reset, self.newborn) + 1
+    }
+
+    /// The factor by which the population eventually grows each day, the
+    /// dominant eigenvalue of the daily transition. A fish at 0 has offspring
+    /// back at 0 after `reset + 1` and `newborn + 1` days, so this is the root
+    /// of `x^-(reset + 1) + x^-(newborn + 1) = 1`, which lies in [1, 2].
+    pub fn growth_rate(&self) -> f64 {
+        let excess = |x: f64| x.powi(-(self.reset as i32 + 1)) + x.powi(-(self.newborn as i32 + 1)) - 1.0;
+        let (mut low, mut high) = (1.0, 2.0);
+
+        for _ in 0..64 {
+            let middle = (low + high) / 2.0;
+
+            if excess(middle) > 0.0 {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        low
+    }
+}
+
+impl Default for Species {
+    fn default() -> Self {
+        Species::new(6, 8)
+    }
+}
+
+impl From<&str> for Species {
+    /// Parse `reset,newborn`.
+    fn from(input: &str) -> Self {
+        let (reset, newborn) = input.split_once(',')
+            .expect("Species are given as reset,newborn.");
+
+        Species::new(
+            reset.trim().parse().expect("Failed to parse reset timer."),
+            newborn.trim().parse().expect("Failed to parse newborn timer."),
+        )
+    }
+}
+
+/// The fish of one species, counted by the value of their timer.
+#[derive(Clone, Debug)]
+pub struct School {
+    pub species: Species,
+    pub bins: Vec<BigUint>,
+}
+
+impl School {
+    pub fn new(species: Species, timers: &[usize]) -> Self {
+        let mut counts = vec![0; species.ages()];
+
+        for &timer in timers {
+            assert!(timer < counts.len(), "Timer {} is out of range for {:?}.", timer, species);
+            counts[timer] += 1;
+        }
+
+        School { species, bins: counts.into_iter().map(BigUint::from).collect() }
+    }
+
+    fn step(&mut self) {
+        let created_fish = self.bins[0].clone();
+
+        self.bins.rotate_left(1);
+        *self.bins.last_mut().unwrap() = BigUint::zero();
+
+        self.bins[self.species.reset] += &created_fish;
+        self.bins[self.species.newborn] += &created_fish;
+    }
+
+    /// The matrix taking the bins of one day to those of the next.
+    pub fn transition(&self) -> Matrix {
+        let mut matrix = Matrix::zero(self.bins.len());
+
+        for age in 1..self.bins.len() {
+            matrix[(age - 1, age)] = BigUint::from(1);
+        }
+
+        matrix[(self.species.reset, 0)] += &BigUint::from(1);
+        matrix[(self.species.newborn, 0)] += &BigUint::from(1);
+
+        matrix
+    }
+
+    pub fn total(&self) -> BigUint {
+        sum(self.bins.iter())
+    }
+
+    /// The number of fish modulo the given modulus.
+    pub fn total_modulo(&self, modulus: u64) -> u64 {
+        self.total().div_rem(modulus).1
+    }
+}
+
+/// Schools of possibly different species, living side by side without
+/// affecting each other.
+#[derive(Clone, Debug, Default)]
+pub struct Population {
+    schools: Vec<School>,
+}
+
+impl Population {
+    pub fn new(schools: Vec<School>) -> Self {
+        Population { schools }
+    }
+
+    /// Advance the simulation by one day.
+    pub fn step(&mut self) {
+        self.schools.iter_mut().for_each(School::step);
+    }
+
+    pub fn simulate(&mut self, days: usize) {
+        (0..days).for_each(|_| self.step());
+    }
+
+    /// Simulate day by day like `simulate`, returning the first day on which
+    /// the total no longer fits in a u64, if any.
+    pub fn simulate_checked(&mut self, days: usize) -> Option<usize> {
+        let mut overflow_day = None;
+
+        for day in 1..=days {
+            self.step();
+
+            if overflow_day.is_none() && self.total().to_u64().is_none() {
+                overflow_day = Some(day);
+            }
+        }
+
+        overflow_day
+    }
+
+    /// Advance the simulation by any number of days at once, by applying the
+    /// daily transition raised to that power. With a modulus all counts are
+    /// only kept modulo it, which keeps them small for any number of days.
+    pub fn jump(&mut self, days: u64, modulus: Option<u64>) {
+        for school in self.schools.iter_mut() {
+            school.bins = school.transition()
+                .pow(days, modulus)
+                .apply(&school.bins, modulus);
+        }
+    }
+
+    pub fn schools(&self) -> &[School] {
+        &self.schools
+    }
+
+    pub fn total(&self) -> BigUint {
+        sum(self.schools.iter().map(School::total).collect::<Vec<_>>().iter())
+    }
+
+    /// The total number of fish modulo the given modulus.
+    pub fn total_modulo(&self, modulus: u64) -> u64 {
+        self.total().div_rem(modulus).1
+    }
+
+    /// The long-term daily growth factor, set by the fastest growing species
+    /// that has any fish.
+    pub fn growth_rate(&self) -> f64 {
+        self.schools.iter()
+            .filter(|school| !school.total().is_zero())
+            .map(|school| school.species.growth_rate())
+            .fold(1.0, f64::max)
+    }
+}
+
+fn sum<'a>(counts: impl Iterator<Item = &'a BigUint>) -> BigUint {
+    counts.fold(BigUint::zero(), |mut sum, count| {
+        sum += count;
+        sum
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(school: &School) -> Vec<u64> {
+        school.bins.iter().map(|count| count.to_u64().unwrap()).collect()
+    }
+
+    fn example() -> Population {
+        Population::new(vec![School::new(Species::default(), &[3, 4, 3, 1, 2])])
+    }
+
+    #[test]
+    fn test_lanternfish_example() {
+        let mut population = example();
+
+        population.simulate(18);
+        assert_eq!(Some(26), population.total().to_u64());
+        population.simulate(80 - 18);
+        assert_eq!(Some(5934), population.total().to_u64());
+        population.simulate(256 - 80);
+        assert_eq!(Some(26984457539), population.total().to_u64());
+    }
+
+    #[test]
+    fn test_newborns_younger_than_reset() {
+        let mut population = Population::new(vec![School::new(Species::new(3, 1), &[0])]);
+
+        population.step();
+        assert_eq!(vec![0, 1, 0, 1], counts(&population.schools()[0]));
+        population.step();
+        assert_eq!(vec![1, 0, 1, 0], counts(&population.schools()[0]));
+    }
+
+    #[test]
+    fn test_jump_matches_simulation() {
+        for species in [Species::default(), Species::new(3, 1), Species::new(2, 4)] {
+            let mut simulated = Population::new(vec![School::new(species, &[0, 1, 1, 3])]);
+            let mut jumped = simulated.clone();
+
+            simulated.simulate(50);
+            jumped.jump(50, None);
+
+            assert_eq!(simulated.schools()[0].bins, jumped.schools()[0].bins);
+        }
+    }
+
+    #[test]
+    fn test_jump_with_modulus() {
+        let mut population = example();
+        population.jump(256, Some(1_000_007));
+        assert_eq!(26984457539 % 1_000_007, population.total_modulo(1_000_007));
+
+        population.jump(1_000_000_000_000_000_000 - 256, Some(1_000_007));
+        assert!(population.total_modulo(1_000_007) < 1_000_007);
+    }
+
+    #[test]
+    fn test_overflow_day_matches_u128_simulation() {
+        let mut bins = [0u128; 9];
+        [3, 4, 3, 1, 2].iter().for_each(|&timer| bins[timer] += 1);
+
+        let mut expected = 0;
+        while bins.iter().sum::<u128>() <= u64::MAX as u128 {
+            bins.rotate_left(1);
+            bins[6] += bins[8];
+            expected += 1;
+        }
+
+        let mut population = example();
+        assert_eq!(Some(expected), population.simulate_checked(expected + 10));
+        assert_eq!(None, example().simulate_checked(expected - 1));
+    }
+
+    #[test]
+    fn test_exact_counts_beyond_u64() {
+        let mut simulated = example();
+        let mut jumped = example();
+
+        simulated.simulate(1000);
+        jumped.jump(1000, None);
+
+        let mut reduced = example();
+        reduced.jump(1000, Some(1_000_007));
+
+        assert_eq!(simulated.total(), jumped.total());
+        assert_eq!(None, jumped.total().to_u64());
+        assert_eq!(jumped.total_modulo(1_000_007), reduced.total_modulo(1_000_007));
+    }
+
+    #[test]
+    fn test_growth_rate_is_dominant_eigenvalue() {
+        assert!((Species::new(0, 0).growth_rate() - 2.0).abs() < 1e-12);
+        // x^2 = x + 1 for a species reproducing like Fibonacci's rabbits.
+        assert!((Species::new(0, 1).growth_rate() - (1.0 + 5f64.sqrt()) / 2.0).abs() < 1e-12);
+
+        let mut population = example();
+        population.simulate(3000);
+        let before = population.total().ln();
+        population.step();
+
+        assert!(((population.total().ln() - before).exp() - population.growth_rate()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_species_are_independent() {
+        let mut population = Population::new(vec![
+            School::new(Species::default(), &[3, 4, 3, 1, 2]),
+            School::new(Species::new(0, 0), &[0]),
+        ]);
+
+        population.simulate(10);
+        assert_eq!(Some(12 + 1024), population.total().to_u64());
+    }
+}
@@ -0,0 +1,92 @@
+/// A small seeded pseudo random number generator (SplitMix64), so that
+/// simulations are reproducible and need nothing outside this crate.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal, by the Box-Muller transform.
+    pub fn normal(&mut self) -> f64 {
+        let u = 1.0 - self.next_f64();
+        let v = self.next_f64();
+
+        (-2.0 * u.ln()).sqrt() * (std::f64::consts::TAU * v).cos()
+    }
+
+    /// The number of successes in `trials` independent trials that each
+    /// succeed with probability `p`. Small cases are sampled trial by trial,
+    /// large ones by the normal approximation, which is then very close.
+    pub fn binomial(&mut self, trials: u64, p: f64) -> u64 {
+        if p <= 0.0 || trials == 0 {
+            return 0;
+        }
+
+        if p >= 1.0 {
+            return trials;
+        }
+
+        let mean = trials as f64 * p;
+        let variance = mean * (1.0 - p);
+
+        if trials <= 64 || variance < 25.0 {
+            return (0..trials).filter(|_| self.next_f64() < p).count() as u64;
+        }
+
+        (mean + variance.sqrt() * self.normal()).round().clamp(0.0, trials as f64) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_sequences_repeat() {
+        let first = (0..5).scan(Rng::new(42), |rng, _| Some(rng.next_u64())).collect::<Vec<_>>();
+        let second = (0..5).scan(Rng::new(42), |rng, _| Some(rng.next_u64())).collect::<Vec<_>>();
+
+        assert_eq!(first, second);
+        assert_ne!(first, (0..5).scan(Rng::new(43), |rng, _| Some(rng.next_u64())).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_sample_moments() {
+        let mut rng = Rng::new(7);
+        let samples = 100_000;
+
+        let uniform_mean = (0..samples).map(|_| rng.next_f64()).sum::<f64>() / samples as f64;
+        assert!((uniform_mean - 0.5).abs() < 0.01);
+
+        let normals = (0..samples).map(|_| rng.normal()).collect::<Vec<_>>();
+        let variance = normals.iter().map(|x| x * x).sum::<f64>() / samples as f64;
+        assert!((variance - 1.0).abs() < 0.02);
+
+        for (trials, p) in [(10, 0.3), (1_000_000, 0.25)] {
+            let mean = (0..1000).map(|_| rng.binomial(trials, p) as f64).sum::<f64>() / 1000.0;
+            let expected = trials as f64 * p;
+            let tolerance = 4.0 * (expected * (1.0 - p) / 1000.0).sqrt();
+
+            assert!((mean - expected).abs() < tolerance);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::population::Species;
+use crate::random::Rng;
+
+/// The probabilities of the possible reproduction intervals, the number of
+/// days from one spawn to the next.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribution {
+    intervals: Vec<(usize, f64)>,
+}
+
+impl Distribution {
+    /// A distribution proportional to the given weights.
+    pub fn new(weights: Vec<(usize, f64)>) -> Self {
+        assert!(weights.iter().all(|&(interval, weight)| interval > 0 && weight >= 0.0),
+            "Intervals have to be positive and weights non-negative.");
+
+        let total = weights.iter().map(|&(_, weight)| weight).sum::<f64>();
+        assert!(total > 0.0, "At least one interval needs a positive weight.");
+
+        Distribution {
+            intervals: weights.into_iter().map(|(interval, weight)| (interval, weight / total)).collect(),
+        }
+    }
+
+    pub fn fixed(interval: usize) -> Self {
+        Distribution::new(vec![(interval, 1.0)])
+    }
+
+    fn longest(&self) -> usize {
+        self.intervals.iter().map(|&(interval, _)| interval).max().unwrap()
+    }
+}
+
+impl From<&str> for Distribution {
+    /// Parse either a single interval `7` or weighted intervals such as
+    /// `6:1,7:2,8:1`.
+    fn from(input: &str) -> Self {
+        let weights = input.split(',')
+            .map(|part| {
+                let (interval, weight) = part.split_once(':').unwrap_or((part, "1"));
+
+                (
+                    interval.trim().parse().expect("Failed to parse interval."),
+                    weight.trim().parse().expect("Failed to parse weight."),
+                )
+            })
+            .collect();
+
+        Distribution::new(weights)
+    }
+}
+
+/// Lanternfish whose reproduction interval is drawn anew after every spawn,
+/// and which die each day with some probability. Newborns always start with
+/// the same timer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StochasticModel {
+    pub newborn: usize,
+    pub intervals: Distribution,
+    pub mortality: f64,
+}
+
+/// The spread of Monte Carlo results, with a 95% confidence interval for the
+/// mean.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl StochasticModel {
+    pub fn new(newborn: usize, intervals: Distribution, mortality: f64) -> Self {
+        assert!((0.0..=1.0).contains(&mortality), "The mortality has to be a probability.");
+
+        StochasticModel { newborn, intervals, mortality }
+    }
+
+    fn ages(&self) -> usize {
+        usize::max(self.newborn, self.intervals.longest() - 1) + 1
+    }
+
+    fn bins(&self, timers: &[usize]) -> Vec<u64> {
+        let mut bins = vec![0; self.ages()];
+
+        for &timer in timers {
+            assert!(timer < bins.len(), "Timer {} is out of range.", timer);
+            bins[timer] += 1;
+        }
+
+        bins
+    }
+
+    /// The expected number of fish on every day from 0 to `days`. Expected
+    /// counts follow the same linear recurrence as the bins themselves, with
+    /// every transition weighted by its probability.
+    pub fn expected(&self, timers: &[usize], days: usize) -> Vec<f64> {
+        let survival = 1.0 - self.mortality;
+        let mut bins = self.bins(timers).into_iter().map(|count| count as f64).collect::<Vec<_>>();
+        let mut totals = vec![bins.iter().sum::<f64>()];
+
+        for _ in 0..days {
+            let spawning = bins[0] * survival;
+
+            bins.rotate_left(1);
+            *bins.last_mut().unwrap() = 0.0;
+            bins.iter_mut().for_each(|count| *count *= survival);
+
+            bins[self.newborn] += spawning;
+            for &(interval, p) in self.intervals.intervals.iter() {
+                bins[interval - 1] += spawning * p;
+            }
+
+            totals.push(bins.iter().sum());
+        }
+
+        totals
+    }
+
+    /// Simulate one possible outcome, returning the number of fish after the
+    /// given number of days.
+    pub fn sample(&self, timers: &[usize], days: usize, rng: &mut Rng) -> u64 {
+        let survival = 1.0 - self.mortality;
+        let mut bins = self.bins(timers);
+
+        for _ in 0..days {
+            let spawning = rng.binomial(bins[0], survival);
+
+            bins.rotate_left(1);
+            *bins.last_mut().unwrap() = 0;
+            bins.iter_mut().for_each(|count| *count = rng.binomial(*count, survival));
+
+            bins[self.newborn] += spawning;
+
+            // Split the spawning fish between the intervals one at a time,
+            // each taking its share of the fish left.
+            let (mut left, mut p_left) = (spawning, 1.0);
+            for &(interval, p) in self.intervals.intervals.iter() {
+                let chosen = rng.binomial(left, p / p_left);
+
+                bins[interval - 1] += chosen;
+                left -= chosen;
+                p_left -= p;
+            }
+            bins[self.intervals.intervals.last().unwrap().0 - 1] += left;
+        }
+
+        bins.iter().sum()
+    }
+
+    /// Run `runs` independent simulations from the given seed.
+    pub fn monte_carlo(&self, timers: &[usize], days: usize, runs: usize, seed: u64) -> Estimate {
+        assert!(runs > 1, "Monte Carlo needs at least two runs.");
+
+        let mut rng = Rng::new(seed);
+        let samples = (0..runs)
+            .map(|_| self.sample(timers, days, &mut rng) as f64)
+            .collect::<Vec<_>>();
+
+        let mean = samples.iter().sum::<f64>() / runs as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (runs - 1) as f64;
+        let margin = 1.96 * (variance / runs as f64).sqrt();
+
+        Estimate { mean, std_dev: variance.sqrt(), low: mean - margin, high: mean + margin }
+    }
+}
+
+impl From<Species> for StochasticModel {
+    /// The deterministic model of the species, without deaths.
+    fn from(species: Species) -> Self {
+        StochasticModel::new(species.newborn, Distribution::fixed(species.reset + 1), 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: [usize; 5] = [3, 4, 3, 1, 2];
+
+    #[test]
+    fn test_deterministic_model_matches_puzzle() {
+        let model = StochasticModel::from(Species::default());
+        let expected = model.expected(&EXAMPLE, 80);
+
+        assert_eq!(26.0, expected[18]);
+        assert_eq!(5934.0, expected[80]);
+        assert_eq!(5934, model.sample(&EXAMPLE, 80, &mut Rng::new(1)));
+    }
+
+    #[test]
+    fn test_expected_with_mortality() {
+        // Every surviving fish splits in two each day.
+        let model = StochasticModel::new(0, Distribution::fixed(1), 0.1);
+        let expected = model.expected(&[0], 10);
+
+        assert!((expected[10] - 1.8f64.powi(10)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_monte_carlo_agrees_with_expectation() {
+        let model = StochasticModel::new(8, Distribution::from("6:1,7:2,8:1"), 0.01);
+        let expected = model.expected(&EXAMPLE, 60)[60];
+        let estimate = model.monte_carlo(&EXAMPLE, 60, 2000, 2021);
+
+        // The margin is 1.96 standard errors, allow a bit more to not depend
+        // on the seed being lucky.
+        let margin = estimate.high - estimate.mean;
+        assert!((estimate.mean - expected).abs() < 2.0 * margin);
+        assert_eq!(estimate, model.monte_carlo(&EXAMPLE, 60, 2000, 2021));
+    }
+}
@@ -0,0 +1,295 @@
+use std::ops::RangeInclusive;
+
+use crate::fuel::{FuelCost, Overflow};
+
+/// The positions where aligning is cheapest, as ascending runs of adjacent
+/// positions, and the fuel it takes at each of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alignment {
+    pub runs: Vec<RangeInclusive<u32>>,
+    pub cost: i64,
+}
+
+impl Alignment {
+    /// The lowest of the optimal positions.
+    pub fn position(&self) -> u32 {
+        *self.runs[0].start()
+    }
+
+    fn push(&mut self, position: u32) {
+        match self.runs.last_mut() {
+            Some(run) if *run.end() + 1 == position => *run = *run.start()..=position,
+            _ => self.runs.push(position..=position),
+        }
+    }
+}
+
+/// The cheapest positions between the crabs to align at.
+///
+/// For a convex cost the total goes down until its slope turns non-negative,
+/// so binary search for the first position where moving on doesn't help, and
+/// collect the positions after it that cost just as much. Any other cost is
+/// evaluated at every position. Positions whose total overflows count as more
+/// expensive than any other, so only an overflowing minimum is an error.
+pub fn minimise<C: FuelCost + ?Sized>(model: &C, crabs: &[(u32, u64)]) -> Result<Alignment, Overflow> {
+    let min = crabs.iter().map(|&(p, _)| p).min().expect("There are no crabs to align.");
+    let max = crabs.iter().map(|&(p, _)| p).max().unwrap();
+
+    if !model.is_convex() {
+        let mut best = Alignment { runs: vec![], cost: i64::MAX };
+
+        for p in min..=max {
+            let Ok(cost) = model.total(crabs, p) else {
+                continue;
+            };
+
+            if cost < best.cost || best.runs.is_empty() {
+                best = Alignment { runs: vec![p..=p], cost };
+            } else if cost == best.cost {
+                best.push(p);
+            }
+        }
+
+        return if best.runs.is_empty() { Err(Overflow) } else { Ok(best) };
+    }
+
+    let (mut low, mut high) = (min, max);
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+
+        if is_rising(model, crabs, middle) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+
+    with_ties(model, crabs, low, max)
+}
+
+/// Whether the convex total doesn't go down from the position to the next.
+///
+/// Either total may overflow, so split the crabs at the position: those up to
+/// it only get further away and the others only closer. If the left side
+/// overflows at the next position, so does everything after it, and if the
+/// right side overflows here, so does everything before. Otherwise all four
+/// partial totals fit and the comparison is exact in i128.
+fn is_rising<C: FuelCost + ?Sized>(model: &C, crabs: &[(u32, u64)], position: u32) -> bool {
+    let (left, right): (Vec<_>, Vec<_>) = crabs.iter().partition(|&&(p, _)| p <= position);
+    let next = position + 1;
+
+    match (model.total(&left, next), model.total(&right, position)) {
+        (Err(_), _) => true,
+        (_, Err(_)) => false,
+        (Ok(left_next), Ok(right_here)) => {
+            let left_here = model.total(&left, position).unwrap_or(left_next);
+            let right_next = model.total(&right, next).unwrap_or(right_here);
+
+            left_next as i128 + right_next as i128 >= left_here as i128 + right_here as i128
+        },
+    }
+}
+
+/// The run of positions from the first minimum of a convex cost on that cost
+/// just as much, up to `max`. Past the run the cost only goes up, so its end
+/// is found by binary search as well.
+fn with_ties<C: FuelCost + ?Sized>(model: &C, crabs: &[(u32, u64)], first: u32, max: u32) -> Result<Alignment, Overflow> {
+    let cost = model.total(crabs, first)?;
+    let (mut low, mut high) = (first, max);
+
+    while low < high {
+        let middle = low + (high - low).div_ceil(2);
+
+        if model.total(crabs, middle) == Ok(cost) {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+
+    Ok(Alignment { runs: vec![first..=low], cost })
+}
+
+/// The alignment for the linear cost.
+///
+/// The total distance to all crabs only decreases while there are more crabs
+/// ahead than behind, so it is minimal at a median. With an even number of
+/// crabs every position between the two middle ones is a median.
+pub fn median_alignment<C: FuelCost + ?Sized>(model: &C, crabs: &[(u32, u64)]) -> Result<Alignment, Overflow> {
+    let mut sorted = crabs.to_vec();
+    sorted.sort_unstable();
+
+    let count = sorted.iter().map(|&(_, count)| count as u128).sum::<u128>();
+    assert!(count > 0, "There are no crabs to align, all counts are zero.");
+
+    let nth = |n: u128| {
+        let mut seen = 0;
+
+        sorted.iter()
+            .find(|&&(_, count)| {
+                seen += count as u128;
+                seen > n
+            })
+            .map(|&(p, _)| p)
+            .expect("There are no crabs to align.")
+    };
+
+    let lower = nth(count.saturating_sub(1) / 2);
+    let upper = nth(count / 2);
+
+    Ok(Alignment {
+        runs: vec![lower..=upper],
+        cost: model.total(crabs, lower)?,
+    })
+}
+
+/// The alignment for the triangular cost.
+///
+/// The cost of a crab at c is (d^2 + d) / 2 with d = |x - c|, so the total
+/// is convex and its slope at x is the sum of (x - c) plus half the number of
+/// crabs on one side minus half of those on the other. That is zero within
+/// half a unit of the mean, so the best integer position is next to it.
+/// Rounding can still put us one off, so walk downhill from there, which the
+/// convexity makes exact.
+pub fn mean_alignment<C: FuelCost + ?Sized>(model: &C, crabs: &[(u32, u64)]) -> Result<Alignment, Overflow> {
+    let sum = crabs.iter().map(|&(p, count)| p as u128 * count as u128).sum::<u128>();
+    let count = crabs.iter().map(|&(_, count)| count as u128).sum::<u128>();
+    assert!(count > 0, "There are no crabs to align, all counts are zero.");
+
+    let floor = (sum / count) as u32;
+    let max = crabs.iter().map(|&(p, _)| p).max().unwrap();
+
+    let mut best = floor;
+    let Ok(mut best_cost) = model.total(crabs, best) else {
+        // The minimum is within one of the mean, but may still fit.
+        return minimise(model, crabs);
+    };
+
+    for step in [-1i64, 1] {
+        loop {
+            let next = best as i64 + step;
+            if next < 0 || next > max as i64 {
+                break;
+            }
+
+            let next_cost = match model.total(crabs, next as u32) {
+                Ok(cost) if cost < best_cost => cost,
+                _ => break,
+            };
+
+            (best, best_cost) = (next as u32, next_cost);
+        }
+    }
+
+    // The walk stops at the first of several equally cheap positions when
+    // going up, but at the last when going down.
+    while best > 0 && model.total(crabs, best - 1) == Ok(best_cost) {
+        best -= 1;
+    }
+
+    with_ties(model, crabs, best, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuel::*;
+    use crate::random::Xorshift;
+
+    fn crabs(positions: &[u32]) -> Vec<(u32, u64)> {
+        positions.iter().map(|&p| (p, 1)).collect()
+    }
+
+    fn example() -> Vec<(u32, u64)> {
+        crabs(&[16, 1, 2, 0, 4, 2, 7, 1, 2, 14])
+    }
+
+    fn brute_force(model: &dyn FuelCost, crabs: &[(u32, u64)]) -> Alignment {
+        let min = crabs.iter().map(|&(p, _)| p).min().unwrap();
+        let max = crabs.iter().map(|&(p, _)| p).max().unwrap();
+        let costs = (min..=max).map(|p| (p, model.total(crabs, p).unwrap())).collect::<Vec<_>>();
+        let cost = costs.iter().map(|&(_, cost)| cost).min().unwrap();
+
+        costs.iter()
+            .filter(|&&(_, c)| c == cost)
+            .fold(Alignment { runs: vec![], cost }, |mut alignment, &(p, _)| {
+                alignment.push(p);
+                alignment
+            })
+    }
+
+    #[test]
+    fn test_example() {
+        assert_eq!(Alignment { runs: vec![2..=2], cost: 37 }, Linear.align(&example()).unwrap());
+        assert_eq!(Alignment { runs: vec![5..=5], cost: 168 }, Triangular.align(&example()).unwrap());
+        assert_eq!(5, minimise(&Triangular, &example()).unwrap().position());
+    }
+
+    #[test]
+    fn test_ties() {
+        assert_eq!(vec![2..=5], Linear.align(&crabs(&[0, 2, 5, 9])).unwrap().runs);
+        assert_eq!(vec![2..=5], minimise(&Linear, &crabs(&[0, 2, 5, 9])).unwrap().runs);
+        assert_eq!(vec![1..=2], Triangular.align(&crabs(&[0, 3])).unwrap().runs);
+        assert_eq!(vec![0..=0, 3..=3], Polynomial(vec![0, 10, -3]).align(&crabs(&[0, 3])).unwrap().runs);
+    }
+
+    #[test]
+    fn test_overflow_is_reported() {
+        assert_eq!(Err(Overflow), Quadratic.align(&[(0, 2), (u32::MAX, 2)]));
+        assert_eq!(
+            Ok(Alignment { runs: vec![2147483647..=2147483648], cost: 9223372032559808513 }),
+            Quadratic.align(&crabs(&[0, u32::MAX]))
+        );
+        assert_eq!(Ok(u32::MAX as i64), Linear.align(&crabs(&[0, u32::MAX])).map(|a| a.cost));
+        assert_eq!(vec![0..=u32::MAX], minimise(&Linear, &crabs(&[0, u32::MAX])).unwrap().runs);
+
+        // Most positions overflow, the best one doesn't.
+        let far = [(0, 9), (3_000_000_000, 1)];
+        let expected = Alignment { runs: vec![300_000_000..=300_000_000], cost: 8_100_000_000_000_000_000 };
+        assert_eq!(Ok(expected.clone()), Quadratic.align(&far));
+        assert_eq!(Ok(expected), Polynomial(vec![0, 0, 1]).align(&far));
+
+        let ends = Polynomial(vec![0, 461_168_601_842_738_805, -1]);
+        assert_eq!(
+            Ok(Alignment { runs: vec![0..=0, 20..=20], cost: 9_223_372_036_854_775_700 }),
+            ends.align(&crabs(&[0, 20]))
+        );
+    }
+
+    #[test]
+    fn test_weights_match_repeated_crabs() {
+        let weighted = [(16, 1), (1, 2), (2, 3), (0, 1), (4, 1), (7, 1), (14, 1)];
+
+        for model in [&Linear as &dyn FuelCost, &Triangular, &Quadratic] {
+            assert_eq!(model.align(&example()), model.align(&weighted));
+            assert_eq!(minimise(model, &example()), minimise(model, &weighted));
+        }
+
+        assert_eq!(vec![9..=9], Linear.align(&[(0, 3), (9, 4)]).unwrap().runs);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let mut rng = Xorshift::new(0x9e3779b97f4a7c15);
+
+        let models: [Box<dyn FuelCost>; 5] = [
+            Box::new(Linear),
+            Box::new(Triangular),
+            Box::new(Quadratic),
+            Box::new(Polynomial(vec![3, 2, 0, 1])),
+            Box::new(Polynomial(vec![0, 10, -1])),
+        ];
+
+        for _ in 0..500 {
+            let len = rng.below(20) as usize + 1;
+            let spread = rng.below(200) as u64 + 1;
+            let crabs = (0..len).map(|_| (rng.below(spread), rng.below(4) as u64 + 1)).collect::<Vec<_>>();
+
+            for model in models.iter() {
+                assert_eq!(brute_force(model.as_ref(), &crabs), model.align(&crabs).unwrap());
+                assert_eq!(brute_force(model.as_ref(), &crabs), minimise(model.as_ref(), &crabs).unwrap());
+            }
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use crate::alignment::Alignment;
+use crate::fuel::{FuelCost, Overflow};
+
+/// How many boxes the Euclidean search splits before settling for the best
+/// position found so far. Only near-ties between lots of positions, as along
+/// the line between two far away crabs, come close to this.
+const MAX_BOXES: usize = 1 << 20;
+
+/// Crabs in one or more dimensions, as positions with the number of crabs at
+/// each of them.
+pub struct Fleet {
+    dims: usize,
+    crabs: Vec<(Vec<u32>, u64)>,
+}
+
+impl Fleet {
+    /// Parse a fleet from the lines of the input. In one dimension the first
+    /// line lists the crabs separated by commas, in more each line has the
+    /// coordinates of a crab separated by commas. Either way a crab can be
+    /// followed by a positive `:count` for a number of crabs at the same
+    /// position.
+    pub fn parse(lines: &[String], dims: usize) -> Self {
+        assert!(dims > 0, "Crabs need at least one dimension.");
+
+        let entries: Vec<&str> = match dims {
+            1 => lines.first().map_or(vec![], |line| line.split(',').collect()),
+            _ => lines.iter().map(|line| line.as_str()).collect(),
+        };
+
+        let crabs = entries.into_iter()
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| {
+                let (position, count) = entry.split_once(':').unwrap_or((entry, "1"));
+                let position = position.split(',')
+                    .map(|num| num.trim().parse::<u32>().expect("Failed to parse number."))
+                    .collect::<Vec<_>>();
+
+                assert_eq!(dims, position.len(), "Expected {} coordinates in '{}'.", dims, entry);
+
+                let count = count.trim().parse::<u64>().expect("Failed to parse count.");
+                assert!(count > 0, "The count in '{}' has to be positive.", entry);
+
+                (position, count)
+            })
+            .collect::<Vec<_>>();
+
+        assert!(!crabs.is_empty(), "There are no crabs to align.");
+
+        Fleet { dims, crabs }
+    }
+
+    /// The positions of the crabs along one axis.
+    pub fn axis(&self, axis: usize) -> Vec<(u32, u64)> {
+        self.crabs.iter().map(|(position, count)| (position[axis], *count)).collect()
+    }
+
+    /// The best alignment for a cost that applies to every axis separately,
+    /// as for the Manhattan distance. The total is then a sum of one total per
+    /// axis, each of which can be minimised on its own.
+    pub fn align_separable(&self, model: &dyn FuelCost) -> Result<(Vec<Alignment>, i64), Overflow> {
+        let alignments = (0..self.dims)
+            .map(|axis| model.align(&self.axis(axis)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let cost = alignments.iter()
+            .try_fold(0i64, |sum, alignment| sum.checked_add(alignment.cost))
+            .ok_or(Overflow)?;
+
+        Ok((alignments, cost))
+    }
+
+    /// The fuel to move all crabs to the target in straight lines, at a unit
+    /// of fuel per unit of distance.
+    pub fn euclidean_total(&self, target: &[f64]) -> f64 {
+        self.crabs.iter()
+            .map(|(position, count)| *count as f64 * distance(position, target))
+            .sum()
+    }
+
+    /// The best position to align at when crabs move in straight lines, and
+    /// the fuel needed. The Euclidean distance doesn't split into axes, so
+    /// first approach the geometric median with Weiszfeld's iteration and walk
+    /// downhill on the grid from there. That is usually the optimum already,
+    /// but a convex total can still have grid positions further away that are
+    /// cheaper, such as along the line between just two crabs. So finally
+    /// prove it with a branch and bound over the bounding box, which stops
+    /// early with the best position so far if there are too many near-ties.
+    pub fn euclidean_alignment(&self) -> (Vec<u32>, f64) {
+        let median = self.geometric_median();
+        let (min, max) = self.bounds();
+        let evaluate = |position: &[u32]| {
+            self.euclidean_total(&position.iter().map(|&c| c as f64).collect::<Vec<_>>())
+        };
+
+        // Start from the best corner of the grid cell around the median.
+        let mut best = (0..1 << self.dims)
+            .map(|corner: usize| (0..self.dims)
+                .map(|axis| {
+                    let coordinate = match corner >> axis & 1 {
+                        0 => median[axis].floor(),
+                        _ => median[axis].ceil(),
+                    };
+
+                    (coordinate as u32).clamp(min[axis], max[axis])
+                })
+                .collect::<Vec<_>>())
+            .map(|position| (evaluate(&position), position))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, position)| position)
+            .unwrap();
+        let mut best_cost = evaluate(&best);
+
+        loop {
+            let next = self.neighbours(&best, &min, &max)
+                .map(|position| (evaluate(&position), position))
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+
+            match next {
+                Some((cost, position)) if cost < best_cost => (best, best_cost) = (position, cost),
+                _ => break,
+            }
+        }
+
+        // No position in a box can cost less than every crab moving just to
+        // the nearest point of the box. The total is convex, so it also lies
+        // above its tangent at the centre of the box, where crabs right at
+        // the centre add no slope. The first bound is tight far from the
+        // median, the second close to it.
+        let lower_bound = |low: &[u32], high: &[u32]| {
+            let nearest = self.crabs.iter()
+                .map(|(position, count)| {
+                    let target = (0..self.dims)
+                        .map(|axis| (position[axis] as f64).clamp(low[axis] as f64, high[axis] as f64))
+                        .collect::<Vec<_>>();
+
+                    *count as f64 * distance(position, &target)
+                })
+                .sum::<f64>();
+
+            let centre = (0..self.dims).map(|axis| (low[axis] as f64 + high[axis] as f64) / 2.0).collect::<Vec<_>>();
+            let mut slope = vec![0.0; self.dims];
+            for (position, count) in self.crabs.iter() {
+                let distance = distance(position, &centre);
+                if distance > 0.0 {
+                    slope.iter_mut()
+                        .zip(position.iter().zip(centre.iter()))
+                        .for_each(|(s, (&p, c))| *s += *count as f64 * (c - p as f64) / distance);
+                }
+            }
+
+            let tangent = self.euclidean_total(&centre) + (0..self.dims)
+                .map(|axis| f64::min(
+                    slope[axis] * (low[axis] as f64 - centre[axis]),
+                    slope[axis] * (high[axis] as f64 - centre[axis]),
+                ))
+                .sum::<f64>();
+
+            f64::max(nearest, tangent)
+        };
+
+        let mut boxes = vec![(min, max)];
+        let mut searched = 0;
+
+        while let Some((low, high)) = boxes.pop() {
+            searched += 1;
+            if searched > MAX_BOXES {
+                break;
+            }
+
+            // Only look for positions that are better beyond rounding errors.
+            if lower_bound(&low, &high) >= best_cost - 1e-12 * best_cost.max(1.0) {
+                continue;
+            }
+
+            if low == high {
+                (best, best_cost) = (low.clone(), evaluate(&low));
+                continue;
+            }
+
+            let axis = (0..self.dims).max_by_key(|&axis| high[axis] - low[axis]).unwrap();
+            let middle = low[axis] + (high[axis] - low[axis]) / 2;
+
+            let mut upper_low = low.clone();
+            upper_low[axis] = middle + 1;
+            let mut lower_high = high.clone();
+            lower_high[axis] = middle;
+
+            boxes.push((upper_low, high));
+            boxes.push((low, lower_high));
+        }
+
+        (best, best_cost)
+    }
+
+    /// The point with the least total distance to the crabs, approximated by
+    /// Weiszfeld's iteration starting from their centroid.
+    fn geometric_median(&self) -> Vec<f64> {
+        let count = self.crabs.iter().map(|&(_, count)| count as f64).sum::<f64>();
+        let mut median = (0..self.dims)
+            .map(|axis| self.crabs.iter().map(|(p, c)| p[axis] as f64 * *c as f64).sum::<f64>() / count)
+            .collect::<Vec<_>>();
+
+        for _ in 0..1000 {
+            let mut numerator = vec![0.0; self.dims];
+            let mut denominator = 0.0;
+
+            for (position, count) in self.crabs.iter() {
+                let distance = distance(position, &median);
+
+                // The iteration is undefined on a crab, skipping it only
+                // affects how close we get, the grid search does the rest.
+                if distance < 1e-12 {
+                    continue;
+                }
+
+                let weight = *count as f64 / distance;
+                numerator.iter_mut().zip(position).for_each(|(n, &c)| *n += weight * c as f64);
+                denominator += weight;
+            }
+
+            if denominator == 0.0 {
+                break;
+            }
+
+            let next = numerator.iter().map(|n| n / denominator).collect::<Vec<_>>();
+            let moved = next.iter().zip(&median).map(|(a, b)| (a - b).powi(2)).sum::<f64>().sqrt();
+            median = next;
+
+            if moved < 1e-9 {
+                break;
+            }
+        }
+
+        median
+    }
+
+    /// The corners of the bounding box of the crabs, which contains the best
+    /// position.
+    fn bounds(&self) -> (Vec<u32>, Vec<u32>) {
+        (0..self.dims)
+            .map(|axis| {
+                let coordinates = self.crabs.iter().map(|(position, _)| position[axis]);
+                (coordinates.clone().min().unwrap(), coordinates.max().unwrap())
+            })
+            .unzip()
+    }
+
+    /// The positions next to the given one, diagonals included, within the
+    /// bounds.
+    fn neighbours<'a>(&self, position: &'a [u32], min: &'a [u32], max: &'a [u32]) -> impl Iterator<Item = Vec<u32>> + 'a {
+        let dims = self.dims;
+
+        (0..3usize.pow(dims as u32))
+            .map(move |offset| (0..dims)
+                .map(|axis| position[axis] as i64 + (offset / 3usize.pow(axis as u32) % 3) as i64 - 1)
+                .collect::<Vec<_>>())
+            .filter(move |next| next.iter().enumerate()
+                .all(|(axis, &c)| (min[axis] as i64..=max[axis] as i64).contains(&c)))
+            .map(|next| next.into_iter().map(|c| c as u32).collect::<Vec<_>>())
+            .filter(move |next| next != position)
+    }
+}
+
+fn distance(position: &[u32], target: &[f64]) -> f64 {
+    position.iter()
+        .zip(target)
+        .map(|(&c, t)| (c as f64 - t).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuel::*;
+    use crate::random::Xorshift;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_parse_weighted() {
+        let fleet = Fleet::parse(&lines("16,1:2,2:3,0"), 1);
+
+        assert_eq!(vec![(16, 1), (1, 2), (2, 3), (0, 1)], fleet.axis(0));
+
+        let fleet = Fleet::parse(&lines("1,2\n3,4:5\n\n"), 2);
+        assert_eq!(vec![(2, 1), (4, 5)], fleet.axis(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "has to be positive")]
+    fn test_parse_rejects_zero_counts() {
+        Fleet::parse(&lines("3:0,5:0"), 1);
+    }
+
+    #[test]
+    fn test_separable_alignment() {
+        let fleet = Fleet::parse(&lines("16,0\n1,5\n2,5\n0,1\n4,5\n2,5\n7,1\n1,1\n2,9\n14,0"), 2);
+        let (alignments, cost) = fleet.align_separable(&Linear).unwrap();
+
+        assert_eq!(2, alignments[0].position());
+        assert_eq!(vec![1..=5], alignments[1].runs);
+        assert_eq!(37 + Linear.total(&fleet.axis(1), 1).unwrap(), cost);
+    }
+
+    #[test]
+    fn test_euclidean_matches_brute_force() {
+        let mut rng = Xorshift::new(0x2545f4914f6cdd1d);
+
+        for _ in 0..100 {
+            let crabs = (0..rng.below(8) + 1)
+                .map(|_| format!("{},{}:{}", rng.below(30), rng.below(30), rng.below(3) + 1))
+                .collect::<Vec<_>>();
+            let fleet = Fleet::parse(&crabs, 2);
+            let (min, max) = fleet.bounds();
+
+            let expected = (min[0]..=max[0])
+                .flat_map(|x| (min[1]..=max[1]).map(move |y| [x as f64, y as f64]))
+                .map(|target| fleet.euclidean_total(&target))
+                .fold(f64::INFINITY, f64::min);
+
+            assert!((expected - fleet.euclidean_alignment().1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_euclidean_with_many_ties() {
+        // Every position on the line between the crabs is optimal.
+        let fleet = Fleet::parse(&lines("0,0\n100000,100000"), 2);
+        let (position, cost) = fleet.euclidean_alignment();
+
+        assert_eq!(position[0], position[1]);
+        assert!((cost - 100000.0 * 2f64.sqrt()).abs() < 1e-6);
+
+        let fleet = Fleet::parse(&lines("0,0\n10000,9999"), 2);
+        assert!((fleet.euclidean_alignment().1 - fleet.euclidean_total(&[0.0, 0.0])).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_euclidean_in_one_dimension_is_linear() {
+        let fleet = Fleet::parse(&lines("16,1,2,0,4,2,7,1,2,14"), 1);
+
+        assert_eq!((vec![2], 37.0), fleet.euclidean_alignment());
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+use crate::alignment::{self, Alignment};
+
+/// The fuel needed doesn't fit in an i64.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the fuel needed overflows a 64-bit integer")
+    }
+}
+
+/// How much fuel a crab burns to move a given distance.
+pub trait FuelCost {
+    /// The fuel for the distance, None if it overflows.
+    fn cost(&self, distance: u32) -> Option<i64>;
+
+    /// Whether the cost grows convexly with the distance, starting with a
+    /// non-negative step. The total over all crabs is then convex in the
+    /// target position, which allows searching for its minimum.
+    fn is_convex(&self) -> bool {
+        false
+    }
+
+    /// The fuel all crabs need to move to the target, given as positions
+    /// with the number of crabs at each.
+    fn total(&self, crabs: &[(u32, u64)], target: u32) -> Result<i64, Overflow> {
+        crabs.iter()
+            .try_fold(0i64, |sum, &(p, count)| {
+                let cost = self.cost(p.abs_diff(target))?;
+                sum.checked_add(cost.checked_mul(i64::try_from(count).ok()?)?)
+            })
+            .ok_or(Overflow)
+    }
+
+    /// The cheapest positions between the crabs to align at, and the fuel
+    /// needed.
+    fn align(&self, crabs: &[(u32, u64)]) -> Result<Alignment, Overflow> {
+        alignment::minimise(self, crabs)
+    }
+}
+
+/// One unit of fuel per step, the cost of part 1.
+pub struct Linear;
+
+/// One more unit of fuel for every further step, the cost of part 2.
+pub struct Triangular;
+
+/// The square of the distance.
+pub struct Quadratic;
+
+/// A polynomial in the distance, given by its coefficients from the
+/// constant term up.
+pub struct Polynomial(pub Vec<i64>);
+
+impl FuelCost for Linear {
+    fn cost(&self, distance: u32) -> Option<i64> {
+        Some(distance as i64)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    fn align(&self, crabs: &[(u32, u64)]) -> Result<Alignment, Overflow> {
+        alignment::median_alignment(self, crabs)
+    }
+}
+
+impl FuelCost for Triangular {
+    fn cost(&self, distance: u32) -> Option<i64> {
+        let distance = distance as i64;
+
+        // Halve whichever factor is even first, so only the result has to fit.
+        match distance % 2 {
+            0 => (distance / 2).checked_mul(distance + 1),
+            _ => distance.checked_mul((distance + 1) / 2),
+        }
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+
+    fn align(&self, crabs: &[(u32, u64)]) -> Result<Alignment, Overflow> {
+        alignment::mean_alignment(self, crabs)
+    }
+}
+
+impl FuelCost for Quadratic {
+    fn cost(&self, distance: u32) -> Option<i64> {
+        let distance = distance as i64;
+
+        distance.checked_mul(distance)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+impl FuelCost for Polynomial {
+    fn cost(&self, distance: u32) -> Option<i64> {
+        self.0.iter().rev().try_fold(0i64, |value, &coefficient| {
+            value.checked_mul(distance as i64)?.checked_add(coefficient)
+        })
+    }
+
+    /// Only recognises polynomials without negative coefficients beyond the
+    /// constant term, which covers the common cases. Anything else is
+    /// treated as non-convex and scanned.
+    fn is_convex(&self) -> bool {
+        self.0.iter().skip(1).all(|&coefficient| coefficient >= 0)
+    }
+}
+
+/// Parse a cost model: `linear`, `triangular`, `quadratic` or a polynomial
+/// such as `poly:0,1,3` for 3d^2 + d.
+pub fn parse_model(input: &str) -> Box<dyn FuelCost> {
+    match input {
+        "linear" => Box::new(Linear),
+        "triangular" => Box::new(Triangular),
+        "quadratic" => Box::new(Quadratic),
+        _ => match input.strip_prefix("poly:") {
+            Some(coefficients) => Box::new(Polynomial(coefficients.split(',')
+                .map(|c| c.trim().parse().expect("Failed to parse coefficient."))
+                .collect())),
+            None => panic!("Unknown cost model '{}', expected linear, triangular, quadratic or poly:c0,c1,...", input),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_costs() {
+        assert_eq!(Some(11), Linear.cost(11));
+        assert_eq!(Some(66), Triangular.cost(11));
+        assert_eq!(Some(121), Quadratic.cost(11));
+        assert_eq!(Some(7 - 2 * 11 + 3 * 121), Polynomial(vec![7, -2, 3]).cost(11));
+        assert!(!parse_model("poly:7,-2,3").is_convex());
+        assert_eq!(Triangular.cost(11).map(|cost| 2 * cost), parse_model("poly:0,1,1").cost(11));
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(Some(9223372034707292160), Triangular.cost(u32::MAX));
+        assert_eq!(Err(Overflow), Triangular.total(&[(0, 2)], u32::MAX));
+        assert_eq!(None, Polynomial(vec![0, 0, 0, 1]).cost(1 << 21));
+        assert_eq!(Err(Overflow), Quadratic.total(&[(0, 1), (u32::MAX, 2)], u32::MAX));
+        assert_eq!(Ok(2 * (u32::MAX as i64)), Linear.total(&[(0, 2), (u32::MAX, 1)], u32::MAX));
+        assert_eq!(Err(Overflow), Linear.total(&[(0, u64::MAX)], 1));
+    }
+}
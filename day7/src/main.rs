@@ -1,41 +1,87 @@
-#![feature(int_abs_diff)]
+mod alignment;
+mod fleet;
+mod fuel;
+#[cfg(test)]
+mod random;
 
+use std::env;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
+use alignment::Alignment;
+use fleet::Fleet;
+use fuel::*;
 
-fn p1_fuel_cost(start: u32, end: u32) -> u32 {
-    start.abs_diff(end)
-}
+fn main() {
+    let mut input = "input.txt".to_owned();
+    let mut models: Vec<String> = vec![];
+    let mut dims = 1;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--model" => models.push(args.next().expect("--model expects a cost model.")),
+            "--dims" => dims = args.next().and_then(|d| d.parse().ok()).expect("--dims expects a number."),
+            _ => input = arg,
+        }
+    }
+
+    assert!((1..=3).contains(&dims), "--dims expects 1, 2 or 3.");
+    let fleet = Fleet::parse(&read_lines(input), dims);
+
+    if models.is_empty() {
+        print_alignment(&fleet, "part 1", &Linear);
+        print_alignment(&fleet, "part 2", &Triangular);
+        return;
+    }
 
-fn p2_fuel_cost(start: u32, end: u32) -> u32 {
-    let diff = start.abs_diff(end);
+    for name in models {
+        if name == "euclidean" {
+            let (position, cost) = fleet.euclidean_alignment();
+            let position = position.iter().map(|c| c.to_string()).collect::<Vec<_>>();
 
-    (diff + 1) * diff / 2
+            println!("Globally closest ({}): {:.3}", name, cost);
+            println!("  Align at position {}", position.join(","));
+        } else {
+            print_alignment(&fleet, &name, parse_model(&name).as_ref());
+        }
+    }
 }
 
-fn main() {
-    let numbers = &read_lines("input.txt")[0];
-    let numbers = numbers.split(',')
-        .map(|num| num.parse::<u32>().expect("Failed to parse number."))
-        .collect::<Vec<_>>();
+fn print_alignment(fleet: &Fleet, label: &str, model: &dyn FuelCost) {
+    let (alignments, cost) = match fleet.align_separable(model) {
+        Ok(alignment) => alignment,
+        Err(error) => {
+            eprintln!("Globally closest ({}): {}", label, error);
+            return;
+        }
+    };
 
-    let min = *numbers.iter().min().unwrap();
-    let max = *numbers.iter().max().unwrap();
+    println!("Globally closest ({}): {}", label, cost);
 
+    if let [alignment] = &alignments[..] {
+        println!("  Align at {}", describe(alignment));
+        return;
+    }
 
-    let p1 = (min..=max)
-        .map(|p| numbers.iter().map(|&n| p1_fuel_cost(p, n)).sum::<u32>())
-        .min()
-        .unwrap();
+    for (axis, alignment) in ["x", "y", "z"].iter().zip(alignments.iter()) {
+        println!("  Align {} at {}", axis, describe(alignment));
+    }
+}
 
-    let p2 = (min..=max)
-        .map(|p| numbers.iter().map(|&n| p2_fuel_cost(p, n)).sum::<u32>())
-        .min()
-        .unwrap();
+fn describe(alignment: &Alignment) -> String {
+    let runs = alignment.runs.iter()
+        .map(|run| match run.start() == run.end() {
+            true => run.start().to_string(),
+            false => format!("{} to {}", run.start(), run.end()),
+        })
+        .collect::<Vec<_>>();
 
-    println!("Globally closest (part 1): {}", p1);
-    println!("Globally closest (part 2): {}", p2);
+    if runs.len() == 1 && alignment.runs[0].start() == alignment.runs[0].end() {
+        format!("position {}", alignment.position())
+    } else {
+        format!("any of the tied positions {}", runs.join(", "))
+    }
 }
 
 // The output is wrapped in a Result to allow matching on errors
@@ -49,7 +95,6 @@ where P: AsRef<Path>, {
 
     io::BufReader::new(file)
         .lines()
-        .into_iter()
         .map(|l| l.expect("Failed to read line."))
         .collect()
 }
@@ -0,0 +1,19 @@
+/// A xorshift generator, so the randomised tests are reproducible.
+pub struct Xorshift(u64);
+
+impl Xorshift {
+    pub fn new(seed: u64) -> Self {
+        assert!(seed != 0, "Xorshift needs a non-zero seed.");
+
+        Xorshift(seed)
+    }
+
+    /// A number below the bound.
+    pub fn below(&mut self, bound: u64) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 % bound) as u32
+    }
+}
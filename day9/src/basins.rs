@@ -0,0 +1,143 @@
+use crate::grid::{Boundary, Grid, Neighborhood};
+
+/// The basins of a heightmap: every cell below 9 belongs to exactly one,
+/// made up of all cells it connects to without crossing a 9.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Basins {
+    /// The basin of every cell, None for the walls of 9.
+    pub labels: Grid<Option<usize>>,
+    /// The number of cells in every basin, by label.
+    pub sizes: Vec<usize>,
+}
+
+impl Basins {
+    /// Label the basins with a single union-find pass, joining every cell
+    /// with its neighbours. Labels are handed out in the order the basins are
+    /// first met, row by row. Padding is not part of any basin, so it
+    /// separates basins just like clipping.
+    pub fn find(grid: &Grid<u32>, neighborhood: &Neighborhood, boundary: &Boundary<u32>) -> Self {
+        let width = grid.width();
+        let mut sets = DisjointSets::new(width * grid.height());
+
+        for (row, col) in grid.cells().filter(|&cell| grid[cell] < 9) {
+            for neighbor in grid.neighbors(row, col, neighborhood, boundary) {
+                if let Some((r, c)) = neighbor.position.filter(|_| *neighbor.value < 9) {
+                    sets.union(row * width + col, r * width + c);
+                }
+            }
+        }
+
+        let mut label_of_root = vec![None; width * grid.height()];
+        let mut sizes = vec![];
+        let mut labels = grid.map(|_| None);
+
+        for (row, col) in grid.cells().filter(|&cell| grid[cell] < 9) {
+            let root = sets.find(row * width + col);
+            let label = *label_of_root[root].get_or_insert_with(|| {
+                sizes.push(0);
+                sizes.len() - 1
+            });
+
+            sizes[label] += 1;
+            *labels.get_mut(row, col).unwrap() = Some(label);
+        }
+
+        Basins { labels, sizes }
+    }
+
+    /// The labels of the `n` largest basins, largest first.
+    pub fn largest(&self, n: usize) -> Vec<usize> {
+        let mut labels = (0..self.sizes.len()).collect::<Vec<_>>();
+        labels.sort_by_key(|&label| std::cmp::Reverse(self.sizes[label]));
+        labels.truncate(n);
+
+        labels
+    }
+}
+
+/// Sets of indices that can be joined, with union by size and path halving
+/// so that neither finding nor joining needs recursion.
+struct DisjointSets {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        DisjointSets { parents: (0..len).collect(), sizes: vec![1; len] }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+
+        if self.sizes[a] < self.sizes[b] {
+            (a, b) = (b, a);
+        }
+
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::example;
+
+    #[test]
+    fn test_example_basins() {
+        let basins = Basins::find(&example(), &Neighborhood::VonNeumann, &Boundary::Clip);
+
+        assert_eq!(vec![3, 9, 14, 9], basins.sizes);
+        assert_eq!(vec![2, 1, 3], basins.largest(3));
+        assert_eq!(Some(&None), basins.labels.get(0, 2));
+        assert_eq!(Some(&Some(2)), basins.labels.get(2, 2));
+        assert_eq!(Some(&Some(3)), basins.labels.get(4, 9));
+    }
+
+    #[test]
+    fn test_large_basin_without_recursion() {
+        // A single winding basin through a 1000x1000 map, which is far too
+        // deep for a recursive flood fill.
+        let lines = (0..1000)
+            .map(|row| match row % 4 {
+                1 => "9".repeat(999) + "0",
+                3 => "0".to_owned() + &"9".repeat(999),
+                _ => "0".repeat(1000),
+            })
+            .collect::<Vec<_>>();
+        let basins = Basins::find(&Grid::from_digits(&lines), &Neighborhood::VonNeumann, &Boundary::Clip);
+
+        assert_eq!(vec![500 * 1000 + 500], basins.sizes);
+    }
+
+    #[test]
+    fn test_wrapped_basins() {
+        // On a torus the basins meet across the edges and become one.
+        let basins = Basins::find(&example(), &Neighborhood::VonNeumann, &Boundary::Wrap);
+        assert_eq!(vec![35], basins.sizes);
+
+        // Diagonals slip between walls that only touch at their corners, the
+        // top left basin for one through the gap between (1, 1) and (2, 0).
+        // Padding is not part of any basin, so it doesn't join them either.
+        let basins = Basins::find(&example(), &Neighborhood::Moore, &Boundary::Pad(0));
+        assert_eq!(vec![35], basins.sizes);
+        assert_eq!(basins, Basins::find(&example(), &Neighborhood::Moore, &Boundary::Clip));
+
+        // Only left and right leaves the runs of each row.
+        let basins = Basins::find(&example(), &Neighborhood::from("0,1;0,-1"), &Boundary::Pad(0));
+        assert_eq!(vec![2, 5, 1, 3, 1, 2, 5, 1, 1, 5, 3, 1, 5], basins.sizes);
+    }
+}
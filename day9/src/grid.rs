@@ -1,73 +1,287 @@
-pub struct Grid {
-    heightmap: Vec<Vec<u32>>,
+use std::fmt;
+use std::ops::Index;
+use std::str::FromStr;
+
+/// Which cells around a cell count as its neighbours, as (row, column)
+/// offsets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// Above, below, left and right.
+    VonNeumann,
+    /// All eight surrounding cells, diagonals included.
+    Moore,
+    Custom(Vec<(isize, isize)>),
 }
 
-impl Grid {
-    pub fn new(lines: Vec<String>) -> Self {
-        let heightmap = lines.into_iter()
-            .map(|l| l.chars()
-                .map(|c| c.to_digit(10).expect("Failed to get digit."))
-                .collect::<Vec<_>>())
-            .collect::<Vec<_>>();
+impl Neighborhood {
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        match self {
+            Neighborhood::VonNeumann => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            Neighborhood::Moore => &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)],
+            Neighborhood::Custom(offsets) => offsets,
+        }
+    }
+}
 
-        Self { heightmap }
+impl From<&str> for Neighborhood {
+    /// Parse `von-neumann`, `moore` or offsets such as `-1,0;1,0`.
+    fn from(input: &str) -> Self {
+        match input {
+            "von-neumann" => Neighborhood::VonNeumann,
+            "moore" => Neighborhood::Moore,
+            _ => Neighborhood::Custom(input.split(';')
+                .map(|offset| {
+                    let (dr, dc) = offset.split_once(',').expect("Offsets are given as row,col.");
+                    (
+                        dr.trim().parse().expect("Failed to parse offset."),
+                        dc.trim().parse().expect("Failed to parse offset."),
+                    )
+                })
+                .collect()),
+        }
     }
+}
+
+/// What lies beyond the edges of the grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Boundary<T> {
+    /// Nothing, cells outside the grid don't exist.
+    Clip,
+    /// The grid again, as on a torus.
+    Wrap,
+    /// The same value everywhere.
+    Pad(T),
+}
 
-    pub fn size_x(&self) -> usize {
-        self.heightmap[0].len()
+impl<T: FromStr> From<&str> for Boundary<T> {
+    /// Parse `clip`, `wrap` or `pad:value`.
+    fn from(input: &str) -> Self {
+        match input {
+            "clip" => Boundary::Clip,
+            "wrap" => Boundary::Wrap,
+            _ => match input.strip_prefix("pad:") {
+                Some(value) => Boundary::Pad(value.trim().parse()
+                    .unwrap_or_else(|_| panic!("Failed to parse padding '{}'.", value))),
+                None => panic!("Unknown boundary '{}', expected clip, wrap or pad:value", input),
+            },
+        }
     }
+}
+
+/// A cell near another one, with its position unless it is padding outside
+/// the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Neighbor<'a, T> {
+    pub position: Option<(usize, usize)>,
+    pub value: &'a T,
+}
 
-    pub fn size_z(&self) -> usize {
-        self.heightmap.len()
+/// A rectangular grid of cells, stored row by row in a single vector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len(), "The cells don't fill a {}x{} grid.", width, height);
+
+        Self { width, height, cells }
     }
 
-    pub fn height_at(&self, row: usize, col: usize) -> u32 {
-        if !self.in_grid(row as i32, col as i32) {
-            return 9;
+    /// Parse a grid with one line per row, turning each character into a
+    /// cell. All rows have to be equally long.
+    pub fn from_chars<F>(lines: &[String], mut parse: F) -> Self
+    where F: FnMut(char) -> T, {
+        let width = lines.first().map_or(0, |l| l.chars().count());
+        let mut cells = Vec::with_capacity(width * lines.len());
+
+        for line in lines {
+            let row = line.chars().map(&mut parse).collect::<Vec<_>>();
+            assert_eq!(width, row.len(), "Rows of different lengths in '{}'.", line);
+
+            cells.extend(row);
         }
 
-        self.heightmap[row][col]
+        Self::new(width, lines.len(), cells)
     }
 
-    pub fn neighbors(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        [(-1i32, 0i32), (1i32, 0i32), (0i32, -1i32), (0i32, 1i32)]
-            .into_iter()
-            .map(|(dr, dc)| (row as i32 + dr, col as i32 + dc))
-            .filter(|&(row, col)| self.in_grid(row, col))
-            .map(|(r, c)| (r as usize, c as usize))
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        self.index(row, col).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        self.index(row, col).map(move |i| &mut self.cells[i])
+    }
+
+    /// The cell at a position that may lie outside the grid, None if there
+    /// is nothing there.
+    pub fn get_beyond<'a>(&'a self, row: isize, col: isize, boundary: &'a Boundary<T>) -> Option<Neighbor<'a, T>> {
+        let (height, width) = (self.height as isize, self.width as isize);
+
+        let (row, col) = match boundary {
+            _ if (0..height).contains(&row) && (0..width).contains(&col) => (row, col),
+            Boundary::Wrap if width > 0 && height > 0 => (row.rem_euclid(height), col.rem_euclid(width)),
+            Boundary::Pad(value) => return Some(Neighbor { position: None, value }),
+            _ => return None,
+        };
+        let position = (row as usize, col as usize);
+
+        Some(Neighbor { position: Some(position), value: &self[position] })
+    }
+
+    /// The neighbours of a cell in the given neighbourhood. On a small grid
+    /// wrapping can reach the same cell more than once, but a cell is never
+    /// its own neighbour.
+    pub fn neighbors<'a>(
+        &'a self,
+        row: usize,
+        col: usize,
+        neighborhood: &Neighborhood,
+        boundary: &'a Boundary<T>,
+    ) -> Vec<Neighbor<'a, T>> {
+        neighborhood.offsets().iter()
+            .filter_map(|&(dr, dc)| self.get_beyond(row as isize + dr, col as isize + dc, boundary))
+            .filter(|neighbor| neighbor.position != Some((row, col)))
             .collect::<Vec<_>>()
     }
 
+    /// The positions of all cells, row by row.
     pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
-        (0..self.size_z())
-            .flat_map(|row| (0..self.size_x()).map(move |col| (row, col)))
+        (0..self.height)
+            .flat_map(|row| (0..self.width).map(move |col| (row, col)))
+    }
+
+    /// A grid of the same shape with every cell transformed.
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where F: FnMut(&T) -> U, {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    fn index(&self, row: usize, col: usize) -> Option<usize> {
+        (row < self.height && col < self.width).then(|| row * self.width + col)
     }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
 
-    fn in_grid(&self, row: i32, col: i32) -> bool {
-        (0..self.size_x() as i32).contains(&col) &&
-        (0..self.size_z() as i32).contains(&row)
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        self.get(row, col).unwrap_or_else(|| panic!("Cell ({}, {}) is outside the grid.", row, col))
     }
 }
 
+impl Grid<u32> {
+    /// Parse a grid of single digits.
+    pub fn from_digits(lines: &[String]) -> Self {
+        Self::from_chars(lines, |c| c.to_digit(10).expect("Failed to get digit."))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The heightmap of the puzzle example in test.txt, for the tests of every
+/// module.
+#[cfg(test)]
+pub(crate) fn example() -> Grid<u32> {
+    Grid::from_digits(&include_str!("../test.txt").lines().map(str::to_owned).collect::<Vec<_>>())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_neighbors() {
-        let grid = Grid::new(vec![
-            "2199943210".to_owned(),
-            "3987894921".to_owned(),
-            "9856789892".to_owned(),
-            "8767896789".to_owned(),
-            "9899965678".to_owned(),
-        ]);
+        let grid = example();
 
         let (row, col) = (0, 0);
-        let neighbors = grid.neighbors(row, col);
+        let neighbors = grid.neighbors(row, col, &Neighborhood::VonNeumann, &Boundary::Clip)
+            .into_iter()
+            .map(|n| n.position.unwrap())
+            .collect::<Vec<_>>();
 
         assert_eq!(2, neighbors.len());
         assert!(neighbors.contains(&(0, 1)));
         assert!(neighbors.contains(&(1, 0)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_neighborhoods_and_boundaries() {
+        let grid = example();
+        let positions = |neighborhood: &Neighborhood, boundary: &Boundary<u32>| grid
+            .neighbors(0, 0, neighborhood, boundary)
+            .into_iter()
+            .map(|n| (n.position, *n.value))
+            .collect::<Vec<_>>();
+
+        assert_eq!(3, positions(&Neighborhood::Moore, &Boundary::Clip).len());
+        assert_eq!(8, grid.neighbors(2, 2, &Neighborhood::Moore, &Boundary::Clip).len());
+        assert_eq!(
+            vec![(Some((4, 0)), 9), (Some((1, 0)), 3), (Some((0, 9)), 0), (Some((0, 1)), 1)],
+            positions(&Neighborhood::VonNeumann, &Boundary::Wrap),
+        );
+        assert_eq!(
+            vec![(None, 5), (Some((1, 0)), 3), (None, 5), (Some((0, 1)), 1)],
+            positions(&Neighborhood::VonNeumann, &Boundary::Pad(5)),
+        );
+        assert_eq!(
+            vec![(Some((2, 1)), 8), (Some((1, 2)), 8)],
+            positions(&Neighborhood::from("2,1;1,2;-1,2"), &Boundary::Clip),
+        );
+        assert_eq!(Boundary::Pad(9), Boundary::<u32>::from("pad:9"));
+        assert_eq!(None, grid.get_beyond(-1, 3, &Boundary::Clip));
+    }
+
+    #[test]
+    fn test_wrapping_never_reaches_the_cell_itself() {
+        let column = Grid::from_digits(&["5".to_owned(), "1".to_owned(), "7".to_owned()]);
+        let neighbors = column.neighbors(1, 0, &Neighborhood::VonNeumann, &Boundary::Wrap);
+
+        assert_eq!(vec![Some((0, 0)), Some((2, 0))], neighbors.iter().map(|n| n.position).collect::<Vec<_>>());
+
+        // So the lowest cell of the column is a low point.
+        assert!(neighbors.iter().all(|n| column[(1, 0)] < *n.value));
+        assert!(column.neighbors(0, 0, &Neighborhood::Moore, &Boundary::Wrap).iter()
+            .all(|n| n.position != Some((0, 0))));
+    }
+
+    #[test]
+    fn test_flat_storage() {
+        let mut grid = example();
+
+        assert_eq!((10, 5), (grid.width(), grid.height()));
+        assert_eq!(Some(&7), grid.get(2, 4));
+        assert_eq!(None, grid.get(5, 0));
+        assert_eq!(None, grid.get(0, 10));
+        assert_eq!(7, grid[(2, 4)]);
+
+        *grid.get_mut(2, 4).unwrap() = 0;
+        let walls = grid.map(|&h| if h == 9 { '#' } else { '.' });
+
+        assert_eq!(Some(&'.'), walls.get(2, 4));
+        assert!(grid.to_string().starts_with("2199943210\n3987894921\n9856089892\n"));
+        assert_eq!(".#...#.#..", walls.to_string().lines().nth(1).unwrap());
+    }
+}
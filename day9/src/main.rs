@@ -1,68 +1,76 @@
+mod basins;
 mod grid;
+mod render;
 
-use std::collections::HashSet;
-use std::fs::File;
+use std::env;
+use std::fs::{self, File};
 use std::io::{self, BufRead};
 use std::path::Path;
+use grid::{Boundary, Grid, Neighborhood};
 
 fn main() {
-    let lines = read_lines("input.txt");
-    let grid = grid::Grid::new(lines);
+    let mut input = "input.txt".to_owned();
+    let mut show = false;
+    let mut colour = false;
+    let mut image: Option<String> = None;
+    let mut neighborhood = Neighborhood::VonNeumann;
+    let mut boundary = Boundary::Clip;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--show" => show = true,
+            "--ansi" => colour = true,
+            "--ppm" => image = Some(args.next().expect("--ppm expects an output path.")),
+            "--neighborhood" => neighborhood = Neighborhood::from(
+                args.next().expect("--neighborhood expects von-neumann, moore or offsets.").as_str()),
+            "--boundary" => boundary = Boundary::from(
+                args.next().expect("--boundary expects clip, wrap or pad:value.").as_str()),
+            _ => input = arg,
+        }
+    }
+
+    let grid = Grid::from_digits(&read_lines(input));
 
     let low_points = grid.cells()
-        .filter(|&(row, col)| grid.neighbors(row, col).into_iter()
-            .all(|(r, c)| grid.height_at(row, col) < grid.height_at(r, c)))
+        .filter(|&(row, col)| grid.neighbors(row, col, &neighborhood, &boundary).into_iter()
+            .all(|neighbor| grid[(row, col)] < *neighbor.value))
         .collect::<Vec<_>>();
 
     let sum_risk_levels = low_points.iter()
-        .map(|&(row, col)| grid.height_at(row, col) + 1)
+        .map(|&cell| grid[cell] + 1)
         .sum::<u32>();
 
     println!("Sum of risk levels: {}", sum_risk_levels);
 
-    let mut visited = low_points.iter()
-        .cloned()
-        .collect::<HashSet<_>>();
+    if show {
+        let mut marked = grid.map(|&h| char::from_digit(h, 10).unwrap());
+        for &(row, col) in low_points.iter() {
+            *marked.get_mut(row, col).unwrap() = '*';
+        }
 
-    let mut basin_sizes = low_points.iter()
-        .map(|&(row, col)| {
-            1 + grid.neighbors(row, col).into_iter()
-                .map(|(r, c)| {
-                    get_basin_size(&grid, &mut visited, r, c)
-                })
-                .sum::<u32>()
-        })
-        .collect::<Vec<_>>();
+        println!("Low points in the {}x{} heightmap:", grid.width(), grid.height());
+        print!("{}", marked);
+    }
 
-    basin_sizes.sort();
-    let p2_ans = basin_sizes.iter().rev().take(3).product::<u32>();
+    let basins = basins::Basins::find(&grid, &neighborhood, &boundary);
+    let p2_ans = basins.largest(3).into_iter()
+        .map(|label| basins.sizes[label])
+        .product::<usize>();
     println!("Answer p2: {}", p2_ans);
-}
 
-fn get_basin_size(
-    grid: &grid::Grid,
-    visited: &mut HashSet<(usize, usize)>,
-    row: usize,
-    col: usize,
-) -> u32 {
-    let neighbors = grid.neighbors(row, col);
+    if colour || image.is_some() {
+        let colours = render::colours(&grid, &basins, &low_points);
 
-    // A point is in the basin, if among its visited neighbors at least one
-    // of them is at most as heigh as this one, and the current height is less
-    // than 9.
-    let in_basin = grid.height_at(row, col) < 9 && neighbors.iter()
-        .filter(|&p| visited.contains(p))
-        .any(|&(r, c)| grid.height_at(r, c) <= grid.height_at(row, col));
+        if colour {
+            print!("{}", render::ansi(&grid, &colours, &low_points));
+        }
 
-    if !in_basin || visited.contains(&(row, col)) {
-        return 0;
+        if let Some(path) = image {
+            fs::write(&path, render::ppm(&colours, 4)).expect("Failed to write image.");
+            println!("Basin map written to {}", path);
+        }
     }
-
-    visited.insert((row, col));
-
-    1 + neighbors.into_iter()
-        .map(|(r, c)| get_basin_size(grid, visited, r, c))
-        .sum::<u32>()
 }
 
 // The output is wrapped in a Result to allow matching on errors
@@ -76,7 +84,6 @@ where P: AsRef<Path>, {
 
     io::BufReader::new(file)
         .lines()
-        .into_iter()
         .map(|l| l.expect("Failed to read line."))
         .collect()
 }
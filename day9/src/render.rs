@@ -0,0 +1,158 @@
+use std::fmt::Write;
+
+use crate::basins::Basins;
+use crate::grid::Grid;
+
+/// A colour with 8 bits per channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+const WALL: Rgb = Rgb(40, 40, 48);
+const LOW_POINT: Rgb = Rgb(255, 255, 255);
+
+/// The colour of every cell: walls of 9 in dark grey, low points in white and
+/// every basin in a hue of its own. The three largest basins are drawn
+/// bright, the others faded, and within a basin higher cells are lighter.
+pub fn colours(grid: &Grid<u32>, basins: &Basins, low_points: &[(usize, usize)]) -> Grid<Rgb> {
+    let largest = basins.largest(3);
+    let is_low = marks(grid, low_points);
+    let mut colours = grid.map(|_| WALL);
+
+    for (row, col) in grid.cells() {
+        let colour = match basins.labels.get(row, col) {
+            _ if is_low.get(row, col) == Some(&true) => LOW_POINT,
+            Some(Some(label)) => {
+                let saturation = if largest.contains(label) { 0.9 } else { 0.25 };
+                let lightness = 0.3 + 0.4 * grid[(row, col)] as f64 / 9.0;
+
+                hsl(*label as f64 * 0.618034, saturation, lightness)
+            },
+            _ => WALL,
+        };
+
+        *colours.get_mut(row, col).unwrap() = colour;
+    }
+
+    colours
+}
+
+/// The heightmap for a terminal, every digit on its colour using 24-bit ANSI
+/// escapes. Low points are marked `*` and walls `#`.
+pub fn ansi(grid: &Grid<u32>, colours: &Grid<Rgb>, low_points: &[(usize, usize)]) -> String {
+    let is_low = marks(grid, low_points);
+    let mut output = String::new();
+
+    for (row, col) in grid.cells() {
+        let Rgb(r, g, b) = *colours.get(row, col).unwrap();
+        let symbol = match grid[(row, col)] {
+            9 => '#',
+            _ if is_low.get(row, col) == Some(&true) => '*',
+            height => char::from_digit(height, 10).unwrap(),
+        };
+
+        write!(output, "\x1b[48;2;{};{};{}m\x1b[38;2;0;0;0m{}", r, g, b, symbol).unwrap();
+
+        if col + 1 == grid.width() {
+            output.push_str("\x1b[0m\n");
+        }
+    }
+
+    output
+}
+
+/// A binary PPM image with every cell drawn as a `scale` by `scale` square.
+pub fn ppm(colours: &Grid<Rgb>, scale: usize) -> Vec<u8> {
+    let (width, height) = (colours.width() * scale, colours.height() * scale);
+    let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+
+    for y in 0..height {
+        for x in 0..width {
+            let Rgb(r, g, b) = *colours.get(y / scale, x / scale).unwrap();
+            image.extend([r, g, b]);
+        }
+    }
+
+    image
+}
+
+/// Which cells are among the given ones, for a quick lookup.
+fn marks(grid: &Grid<u32>, cells: &[(usize, usize)]) -> Grid<bool> {
+    let mut marks = grid.map(|_| false);
+    for &(row, col) in cells {
+        *marks.get_mut(row, col).expect("Cell outside the grid.") = true;
+    }
+
+    marks
+}
+
+/// Convert a hue in turns, which wraps around, with saturation and lightness
+/// in [0, 1].
+fn hsl(hue: f64, saturation: f64, lightness: f64) -> Rgb {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue.rem_euclid(1.0) * 6.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |c: f64| ((c + m) * 255.0).round() as u8;
+
+    Rgb(channel(r), channel(g), channel(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{example, Boundary, Neighborhood};
+
+    #[test]
+    fn test_colours() {
+        let grid = example();
+        let basins = Basins::find(&grid, &Neighborhood::VonNeumann, &Boundary::Clip);
+        let colours = colours(&grid, &basins, &[(0, 1), (0, 9), (2, 2), (4, 6)]);
+
+        assert_eq!(Some(&WALL), colours.get(0, 2));
+        assert_eq!(Some(&LOW_POINT), colours.get(2, 2));
+
+        // Cells of the same basin and height share a colour, different
+        // basins don't.
+        assert_eq!(colours.get(0, 8), colours.get(1, 9));
+        assert_ne!(colours.get(0, 0), colours.get(0, 6));
+
+        // The smallest basin is faded, so its channels are closer together.
+        let spread = |&Rgb(r, g, b): &Rgb| r.max(g).max(b) - r.min(g).min(b);
+        assert!(spread(colours.get(0, 0).unwrap()) < spread(colours.get(1, 2).unwrap()));
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_eq!(Rgb(255, 0, 0), hsl(0.0, 1.0, 0.5));
+        assert_eq!(Rgb(0, 0, 255), hsl(2.0 / 3.0, 1.0, 0.5));
+        assert_eq!(Rgb(128, 128, 128), hsl(0.3, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_outputs() {
+        let grid = example();
+        let basins = Basins::find(&grid, &Neighborhood::VonNeumann, &Boundary::Clip);
+        let colours = colours(&grid, &basins, &[(0, 1)]);
+
+        let ansi = ansi(&grid, &colours, &[(0, 1)]);
+        assert_eq!(5, ansi.lines().count());
+        assert!(ansi.starts_with("\x1b[48;2;"));
+        assert!(ansi.contains("m*\x1b"));
+        assert!(ansi.contains("m#\x1b"));
+
+        let image = ppm(&colours, 2);
+        let header = b"P6\n20 10\n255\n";
+        assert_eq!(header.len() + 20 * 10 * 3, image.len());
+        assert_eq!(&header[..], &image[..header.len()]);
+        assert_eq!([WALL.0, WALL.1, WALL.2], image[header.len() + 12..header.len() + 15]);
+    }
+}